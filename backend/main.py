@@ -1,4 +1,4 @@
-from fastapi import FastAPI, HTTPException, UploadFile, File
+from fastapi import FastAPI, HTTPException, UploadFile, File, BackgroundTasks
 from fastapi.middleware.cors import CORSMiddleware
 from fastapi.responses import JSONResponse
 from fastapi.staticfiles import StaticFiles
@@ -6,6 +6,7 @@ from pydantic import BaseModel
 from typing import List, Optional, Dict, Any
 import uvicorn
 import os
+import signal
 import sys
 from pathlib import Path
 
@@ -80,6 +81,25 @@ async def health_check():
     """Health check endpoint"""
     return {"status": "healthy"}
 
+# Version endpoint, checked by the desktop app against the oldest it supports
+@app.get("/version")
+async def version():
+    """Semantic version of this API"""
+    return {"version": app.version}
+
+# Ping endpoint, used by the desktop app to measure round-trip latency
+@app.get("/ping")
+async def ping():
+    """Answer without touching any state"""
+    return {"status": "ok"}
+
+# Shutdown endpoint, used by the desktop app to stop the backend gracefully
+@app.post("/shutdown")
+async def shutdown(background_tasks: BackgroundTasks):
+    """Exit after the response has been sent"""
+    background_tasks.add_task(signal.raise_signal, signal.SIGINT)
+    return {"status": "shutting down"}
+
 # API key status endpoint
 @app.get("/api-key-status")
 async def api_key_status():
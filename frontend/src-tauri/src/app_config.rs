@@ -0,0 +1,201 @@
+//! Settings the user can change from inside the app, kept in `settings.toml`
+//! in the app config directory. This is separate from the backend's own
+//! `config.toml` that `config_watch` hot-reloads.
+//!
+//! The file is created on first launch from `plugins.backend` in
+//! `tauri.conf.json`; after that it takes precedence. Keys missing from it get
+//! the built-in defaults. The locale (`set_locale`) and the last port the
+//! backend was healthy on are kept in the store instead.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tokio::sync::Mutex;
+
+use crate::error::EchoError;
+use crate::port::PreferredPort;
+use crate::sidecar::{self, BackendStartupConfig, ShutdownConfig, SidecarConfig, SidecarEnv};
+use crate::watchdog::WatchdogConfig;
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    /// `error`, `warn`, `info`, `debug`, `trace` or `off`.
+    pub log_level: String,
+    /// Serve the backend over HTTPS with a locally issued certificate.
+    pub tls: bool,
+    /// Port to try first, instead of the last one the backend was healthy on.
+    pub preferred_port: Option<u16>,
+    /// Environment variables for the backend, on top of those from `.env`.
+    pub env: HashMap<String, String>,
+    pub startup: BackendStartupConfig,
+    pub shutdown: ShutdownConfig,
+    pub watchdog: WatchdogConfig,
+    /// Shown before the title passed to `set_window_title`.
+    pub title_prefix: String,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            log_level: "info".into(),
+            tls: false,
+            preferred_port: None,
+            env: HashMap::new(),
+            startup: BackendStartupConfig::default(),
+            shutdown: ShutdownConfig::default(),
+            watchdog: WatchdogConfig::default(),
+            title_prefix: "Echo".into(),
+        }
+    }
+}
+
+impl AppConfig {
+    /// Read `settings.toml`, creating it from `tauri.conf.json` if it doesn't exist.
+    pub fn load(app: &AppHandle) -> Self {
+        let path = match settings_path(app) {
+            Ok(path) => path,
+            Err(e) => {
+                log::warn!("Could not resolve settings path: {}", e);
+                return Self::from_tauri_conf(app);
+            }
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(text) => toml::from_str(&text).unwrap_or_else(|e| {
+                log::warn!("Ignoring invalid {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let config = Self::from_tauri_conf(app);
+                if let Err(e) = config.save(app) {
+                    log::warn!("Failed to create {}: {}", path.display(), e);
+                }
+                config
+            }
+            Err(e) => {
+                log::warn!("Failed to read {}: {}", path.display(), e);
+                Self::from_tauri_conf(app)
+            }
+        }
+    }
+
+    fn from_tauri_conf(app: &AppHandle) -> Self {
+        Self {
+            tls: sidecar::load_backend_config::<SidecarConfig>(app).tls,
+            startup: sidecar::load_backend_config(app),
+            shutdown: sidecar::load_backend_config(app),
+            watchdog: sidecar::load_backend_config(app),
+            ..Self::default()
+        }
+    }
+
+    fn save(&self, app: &AppHandle) -> Result<(), EchoError> {
+        let path = settings_path(app).map_err(|e| e.to_string())?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let text = toml::to_string_pretty(self)?;
+        std::fs::write(&path, text)?;
+        Ok(())
+    }
+
+    /// A copy with the `env` values masked, for dumps that may be shared.
+    pub fn redacted(&self) -> Self {
+        let env = self
+            .env
+            .keys()
+            .map(|key| (key.clone(), "[redacted]".into()))
+            .collect();
+        Self {
+            env,
+            ..self.clone()
+        }
+    }
+
+    pub fn log_level(&self) -> Result<log::LevelFilter, EchoError> {
+        log::LevelFilter::from_str(&self.log_level)
+            .map_err(|_| EchoError::invalid(format!("invalid log level {:?}", self.log_level)))
+    }
+}
+
+pub fn settings_path(app: &AppHandle) -> tauri::Result<PathBuf> {
+    Ok(app.path().app_config_dir()?.join("settings.toml"))
+}
+
+#[derive(Serialize)]
+pub struct ConfigChangeResult {
+    /// Changed settings that only take effect after the app is restarted.
+    pub requires_restart: Vec<&'static str>,
+}
+
+#[tauri::command]
+pub async fn read_config(
+    state: tauri::State<'_, Mutex<AppConfig>>,
+) -> Result<AppConfig, EchoError> {
+    Ok(state.lock().await.clone())
+}
+
+/// Save `config` and apply what can be applied right away: the log level, the
+/// title prefix (on the next `set_window_title`), and the startup settings and
+/// environment for the next time the backend is spawned.
+#[tauri::command]
+pub async fn write_config(
+    config: AppConfig,
+    handle: AppHandle,
+) -> Result<ConfigChangeResult, EchoError> {
+    let level = config.log_level()?;
+    config.save(&handle)?;
+
+    let state = handle.state::<Mutex<AppConfig>>();
+    let mut current = state.lock().await;
+    if config.log_level != current.log_level {
+        log::set_max_level(level);
+    }
+    if config.startup != current.startup {
+        *handle.state::<Mutex<BackendStartupConfig>>().lock().await = config.startup.clone();
+    }
+    if config.env != current.env {
+        handle
+            .state::<Mutex<SidecarEnv>>()
+            .lock()
+            .await
+            .replace_config(&current.env, &config.env);
+    }
+
+    // Compared against what is in effect, not what was last saved
+    let mut requires_restart = Vec::new();
+    if config.tls != handle.state::<Mutex<SidecarConfig>>().lock().await.tls {
+        requires_restart.push("tls");
+    }
+    if config.preferred_port != handle.state::<PreferredPort>().0 {
+        requires_restart.push("preferred_port");
+    }
+    if config.shutdown != *handle.state::<ShutdownConfig>() {
+        requires_restart.push("shutdown");
+    }
+    if config.watchdog != *handle.state::<WatchdogConfig>() {
+        requires_restart.push("watchdog");
+    }
+    *current = config;
+    Ok(ConfigChangeResult { requires_restart })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacted_keeps_env_keys_only() {
+        let config = AppConfig {
+            env: HashMap::from([("DASHSCOPE_API_KEY".to_string(), "sk-123".to_string())]),
+            ..AppConfig::default()
+        };
+        let redacted = config.redacted();
+        assert_eq!(redacted.env["DASHSCOPE_API_KEY"], "[redacted]");
+        assert_eq!(redacted.log_level, config.log_level);
+        assert!(!serde_json::to_string(&redacted).unwrap().contains("sk-123"));
+    }
+}
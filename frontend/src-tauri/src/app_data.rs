@@ -0,0 +1,251 @@
+use std::path::Path;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_opener::OpenerExt;
+use tauri_plugin_store::StoreExt;
+use tokio::sync::Mutex;
+
+use crate::app_config::{self, AppConfig};
+use crate::backend_proxy::BackendClient;
+use crate::error::EchoError;
+use crate::port::{self, PortState};
+use crate::recent_files::RecentFiles;
+use crate::sidecar::{self, SidecarConfig, SidecarEnv};
+use crate::tasks::TaskScheduler;
+use crate::tls;
+use crate::STORE_FILE;
+
+#[cfg(not(test))]
+fn remove_dir_all(dir: &Path) -> std::io::Result<()> {
+    std::fs::remove_dir_all(dir)
+}
+
+#[cfg(not(test))]
+fn remove_file(path: &Path) -> std::io::Result<()> {
+    std::fs::remove_file(path)
+}
+
+/// Unit tests must never wipe the machine's app data.
+#[cfg(test)]
+fn remove_dir_all(_dir: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+fn remove_file(_path: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Factory reset: stop the backend, wipe everything the app has stored, both
+/// on disk and in memory and including `settings.toml` and `.env`, and start
+/// the backend again. Every step runs even if
+/// an earlier one failed; the failures are reported together.
+#[tauri::command]
+pub async fn clear_app_data(app: AppHandle) -> Result<(), EchoError> {
+    log::info!("Clearing app data");
+    let mut errors = Vec::new();
+
+    sidecar::shutdown(&app).await;
+
+    // The store keeps its contents in memory and would write them back
+    match app.store(STORE_FILE) {
+        Ok(store) => {
+            store.clear();
+            if let Err(e) = store.save() {
+                errors.push(format!("failed to save cleared store: {}", e));
+            }
+        }
+        Err(e) => errors.push(format!("failed to open store: {}", e)),
+    }
+
+    match app.path().app_data_dir() {
+        Ok(dir) => match remove_dir_all(&dir) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => errors.push(format!("failed to remove {}: {}", dir.display(), e)),
+        },
+        Err(e) => errors.push(format!("failed to resolve app data directory: {}", e)),
+    }
+
+    // On macOS and Windows these were in the data directory, elsewhere they
+    // are in a separate config directory
+    for path in [app_config::settings_path(&app), sidecar::env_path(&app)] {
+        let path = match path {
+            Ok(path) => path,
+            Err(e) => {
+                errors.push(format!("failed to resolve app config directory: {}", e));
+                continue;
+            }
+        };
+        match remove_file(&path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => errors.push(format!("failed to remove {}: {}", path.display(), e)),
+        }
+    }
+
+    #[cfg(desktop)]
+    if let Err(e) = crate::windows::reset_window_state(app.clone()) {
+        errors.push(format!("failed to reset window state: {}", e));
+    }
+
+    // Back to the defaults from tauri.conf.json, which also recreates settings.toml
+    let config = AppConfig::load(&app);
+    *app.state::<Mutex<SidecarEnv>>().lock().await = SidecarEnv::load(&app);
+    app.state::<Mutex<SidecarConfig>>().lock().await.tls = config.tls;
+    if let Err(e) = app_config::write_config(config, app.clone()).await {
+        errors.push(format!("failed to reset settings: {}", e));
+    }
+
+    app.state::<RecentFiles>().reload(&app);
+    #[cfg(feature = "clipboard_history")]
+    app.state::<crate::clipboard_history::ClipboardHistory>()
+        .reload(&app);
+    #[cfg(desktop)]
+    app.state::<crate::crash::CrashReportState>().clear();
+    if let Err(e) = app
+        .state::<Mutex<TaskScheduler>>()
+        .lock()
+        .await
+        .reload(&app)
+        .await
+    {
+        errors.push(format!("failed to reset scheduled tasks: {}", e));
+    }
+
+    // The local CA went with the data directory
+    let client = app.state::<BackendClient>();
+    if app.state::<Mutex<SidecarConfig>>().lock().await.tls {
+        match tls::ensure(&app) {
+            Ok(pem) => client.set_root_cert(pem),
+            Err(e) => errors.push(format!("failed to recreate TLS certificates: {}", e)),
+        }
+    }
+    if let Err(e) = client.reload(&app) {
+        errors.push(format!("failed to rebuild backend client: {}", e));
+    }
+
+    match port::choose_port(&app) {
+        Ok(port) => {
+            app.state::<Mutex<PortState>>().lock().await.0 = Some(port);
+            if let Err(e) = sidecar::spawn(&app, port).await {
+                errors.push(format!("failed to restart sidecar: {}", e));
+            }
+        }
+        Err(e) => errors.push(e.to_string()),
+    }
+
+    if !errors.is_empty() {
+        return Err(errors.join("; ").into());
+    }
+    let _ = app.emit("data-cleared", ());
+    Ok(())
+}
+
+/// Show the app config directory in the system file manager, creating it first
+/// if the app hasn't written anything there yet.
+#[tauri::command]
+pub fn open_config_dir(app: AppHandle) -> Result<(), EchoError> {
+    let dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("failed to resolve config directory: {}", e))?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| EchoError::io(format!("failed to create {}: {}", dir.display(), e)))?;
+
+    let with = if cfg!(target_os = "linux") {
+        Some("xdg-open")
+    } else {
+        None
+    };
+    app.opener()
+        .open_path(dir.to_string_lossy(), with)
+        .map_err(|e| format!("failed to open {}: {}", dir.display(), e).into())
+}
+
+#[derive(Serialize)]
+pub struct DiskUsage {
+    pub data_dir_bytes: u64,
+    pub log_dir_bytes: u64,
+    pub cache_dir_bytes: u64,
+}
+
+/// Total size of the files under `dir`; zero if it doesn't exist.
+fn dir_size(dir: &Path) -> u64 {
+    walkdir::WalkDir::new(dir)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+#[tauri::command]
+pub fn get_disk_usage(app: AppHandle) -> Result<DiskUsage, EchoError> {
+    let path = app.path();
+    Ok(DiskUsage {
+        data_dir_bytes: dir_size(&path.app_data_dir().map_err(|e| e.to_string())?),
+        log_dir_bytes: dir_size(&path.app_log_dir().map_err(|e| e.to_string())?),
+        cache_dir_bytes: dir_size(&path.app_cache_dir().map_err(|e| e.to_string())?),
+    })
+}
+
+/// Empty the app cache directory and return how many bytes were freed.
+#[tauri::command]
+pub fn clear_cache(app: AppHandle) -> Result<u64, EchoError> {
+    let dir = app.path().app_cache_dir().map_err(|e| e.to_string())?;
+    let entries = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => {
+            return Err(EchoError::io(format!(
+                "failed to read {}: {}",
+                dir.display(),
+                e
+            )))
+        }
+    };
+
+    let mut freed = 0;
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        let size = dir_size(&path);
+        let removed = if entry.file_type().is_ok_and(|t| t.is_dir()) {
+            std::fs::remove_dir_all(&path)
+        } else {
+            std::fs::remove_file(&path)
+        };
+        match removed {
+            Ok(()) => freed += size,
+            Err(e) => log::warn!("Failed to remove {}: {}", path.display(), e),
+        }
+    }
+    log::info!("Cleared {} bytes from {}", freed, dir.display());
+    Ok(freed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dir_size_sums_nested_files() {
+        let dir = std::env::temp_dir().join(format!("echo-disk-usage-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("nested/deeper")).unwrap();
+        std::fs::write(dir.join("a.bin"), vec![0u8; 1000]).unwrap();
+        std::fs::write(dir.join("nested/b.bin"), vec![0u8; 234]).unwrap();
+        std::fs::write(dir.join("nested/deeper/c.bin"), vec![0u8; 5]).unwrap();
+
+        let size = dir_size(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(size, 1239);
+    }
+
+    #[test]
+    fn missing_dir_is_empty() {
+        let dir = std::env::temp_dir().join(format!("echo-missing-{}", uuid::Uuid::new_v4()));
+        assert_eq!(dir_size(&dir), 0);
+    }
+}
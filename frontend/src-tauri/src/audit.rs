@@ -0,0 +1,239 @@
+//! A record of every command the frontend invokes, for deployments that need
+//! an audit trail. Only built with the `audit_log` feature.
+//!
+//! Entries are taken as commands are dispatched, so they say whether the
+//! command exists but not what it returned.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::ipc::{Invoke, InvokeBody};
+use tauri::{AppHandle, Manager, State, Wry};
+
+use crate::dialog;
+use crate::error::EchoError;
+use crate::logs::now_ms;
+
+/// Oldest entries are dropped past this many.
+const MAX_ENTRIES: usize = 1000;
+
+/// Argument names whose values are replaced with `REDACTED`, matched
+/// case-insensitively against any part of the name. Names ending in `key`,
+/// such as `api_key`, are redacted too.
+const SENSITIVE_ARGS: &[&str] = &[
+    "password",
+    "secret",
+    "token",
+    "plaintext",
+    "ciphertext",
+    "authorization",
+];
+/// Matched against the whole name: `set_sidecar_env`'s `value` and the `env`
+/// table in `write_config`'s config.
+const SENSITIVE_NAMES: &[&str] = &["value", "env"];
+const REDACTED: &str = "[redacted]";
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditResult {
+    Dispatched,
+    UnknownCommand,
+}
+
+#[derive(Clone, Serialize)]
+pub struct AuditEntry {
+    pub timestamp_ms: u64,
+    pub command: String,
+    pub args_json: String,
+    pub result: AuditResult,
+}
+
+#[derive(Default)]
+pub struct AuditLog(Mutex<VecDeque<AuditEntry>>);
+
+impl AuditLog {
+    fn push(&self, entry: AuditEntry) {
+        let mut entries = self.0.lock().unwrap();
+        if entries.len() == MAX_ENTRIES {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+}
+
+fn is_sensitive(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name.ends_with("key")
+        || SENSITIVE_NAMES.contains(&name.as_str())
+        || SENSITIVE_ARGS
+            .iter()
+            .any(|sensitive| name.contains(sensitive))
+}
+
+fn redact(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(fields) => {
+            for (name, value) in fields {
+                if is_sensitive(name) {
+                    *value = REDACTED.into();
+                } else {
+                    redact(value);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact),
+        // Request bodies, e.g. `proxy_request`'s, arrive as JSON text
+        serde_json::Value::String(text) => {
+            let mut body = match serde_json::from_str(text) {
+                Ok(body @ (serde_json::Value::Object(_) | serde_json::Value::Array(_))) => body,
+                _ => return,
+            };
+            redact(&mut body);
+            *text = body.to_string();
+        }
+        _ => {}
+    }
+}
+
+fn args_json(body: &InvokeBody) -> String {
+    match body {
+        InvokeBody::Json(args) => {
+            let mut args = args.clone();
+            redact(&mut args);
+            args.to_string()
+        }
+        InvokeBody::Raw(bytes) => format!("\"<{} bytes>\"", bytes.len()),
+    }
+}
+
+/// Record every invocation before passing it on to `handler`.
+pub fn wrap(
+    handler: impl Fn(Invoke<Wry>) -> bool + Send + Sync + 'static,
+) -> impl Fn(Invoke<Wry>) -> bool + Send + Sync + 'static {
+    move |invoke| {
+        let command = invoke.message.command().to_string();
+        let args_json = args_json(invoke.message.payload());
+        let app = invoke.message.webview_ref().app_handle().clone();
+        let found = handler(invoke);
+        app.state::<AuditLog>().push(AuditEntry {
+            timestamp_ms: now_ms(),
+            command,
+            args_json,
+            result: if found {
+                AuditResult::Dispatched
+            } else {
+                AuditResult::UnknownCommand
+            },
+        });
+        found
+    }
+}
+
+/// The `limit` most recent entries, oldest first.
+#[tauri::command]
+pub fn get_audit_log(limit: usize, log: State<'_, AuditLog>) -> Vec<AuditEntry> {
+    let entries = log.0.lock().unwrap();
+    let skip = entries.len().saturating_sub(limit);
+    entries.iter().skip(skip).cloned().collect()
+}
+
+/// Save every entry as newline-delimited JSON to `path`, which must have been
+/// chosen with `save_file` or inside a folder from `pick_directory`.
+#[tauri::command]
+pub fn export_audit_log(
+    path: String,
+    app: AppHandle,
+    log: State<'_, AuditLog>,
+) -> Result<(), EchoError> {
+    let path = PathBuf::from(path);
+    if !dialog::is_picked(&app, &path) {
+        log::warn!("Refusing to export the audit log to {}", path.display());
+        return Err(EchoError::Unauthorized);
+    }
+    let entries: Vec<AuditEntry> = log.0.lock().unwrap().iter().cloned().collect();
+    write_ndjson(&path, &entries).map_err(|e| {
+        EchoError::io(format!(
+            "Failed to write the audit log to {}: {}",
+            path.display(),
+            e
+        ))
+    })
+}
+
+fn write_ndjson(path: &Path, entries: &[AuditEntry]) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    for entry in entries {
+        serde_json::to_writer(&mut out, entry)?;
+        writeln!(out)?;
+    }
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn secrets_are_redacted_at_any_depth() {
+        let mut args = json!({
+            "key": "DASHSCOPE_API_KEY",
+            "value": "sk-123",
+            "config": {
+                "locale": "en-US",
+                "env": { "HF_TOKEN": "hf_456" },
+                "proxy": { "Password": "hunter2" },
+            },
+            "keyId": "backup-1",
+            "items": [{ "apiKey": "sk-789" }],
+        });
+        redact(&mut args);
+        assert_eq!(
+            args,
+            json!({
+                "key": REDACTED,
+                "value": REDACTED,
+                "config": {
+                    "locale": "en-US",
+                    "env": REDACTED,
+                    "proxy": { "Password": REDACTED },
+                },
+                "keyId": "backup-1",
+                "items": [{ "apiKey": REDACTED }],
+            })
+        );
+    }
+
+    #[test]
+    fn json_bodies_in_strings_are_redacted() {
+        let mut args = json!({
+            "req": {
+                "method": "POST",
+                "path": "/settings",
+                "body": r#"{"dashscope_key":"sk-123","model":"qwen"}"#,
+                "headers": { "Authorization": "Bearer abc" },
+            },
+        });
+        redact(&mut args);
+
+        let body: serde_json::Value =
+            serde_json::from_str(args["req"]["body"].as_str().unwrap()).unwrap();
+        assert_eq!(body, json!({ "dashscope_key": REDACTED, "model": "qwen" }));
+        assert_eq!(args["req"]["headers"]["Authorization"], REDACTED);
+        assert_eq!(args["req"]["path"], "/settings");
+    }
+
+    #[test]
+    fn args_json_redacts_json_and_summarises_raw_bodies() {
+        let args = args_json(&InvokeBody::Json(json!({ "token": "abc", "limit": 5 })));
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&args).unwrap(),
+            json!({ "token": REDACTED, "limit": 5 })
+        );
+        assert_eq!(args_json(&InvokeBody::Raw(vec![0; 3])), "\"<3 bytes>\"");
+    }
+}
@@ -0,0 +1,59 @@
+//! Launching the app on login. Autostart stays off until the frontend calls
+//! `set_autostart(true)`, normally in response to the one-time
+//! `show-autostart-prompt` event sent on first launch.
+
+use tauri::plugin::TauriPlugin;
+use tauri::webview::{PageLoadEvent, PageLoadPayload};
+use tauri::{AppHandle, Emitter, Runtime, Webview};
+use tauri_plugin_autostart::{MacosLauncher, ManagerExt};
+use tauri_plugin_store::StoreExt;
+
+use crate::error::EchoError;
+use crate::STORE_FILE;
+
+/// Set once the user has answered the autostart prompt either way.
+const PROMPTED_KEY: &str = "autostart_prompted";
+
+pub fn plugin<R: Runtime>() -> TauriPlugin<R> {
+    tauri_plugin_autostart::init(MacosLauncher::LaunchAgent, None)
+}
+
+/// Ask the main window's frontend about autostart once it has loaded, unless
+/// the user already answered.
+pub fn prompt_on_first_launch<R: Runtime>(webview: &Webview<R>, payload: &PageLoadPayload<'_>) {
+    if webview.label() != "main" || payload.event() != PageLoadEvent::Finished {
+        return;
+    }
+    let prompted = webview
+        .store(STORE_FILE)
+        .ok()
+        .and_then(|store| store.get(PROMPTED_KEY))
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false);
+    if !prompted {
+        let _ = webview.emit("show-autostart-prompt", ());
+    }
+}
+
+#[tauri::command]
+pub fn set_autostart(app: AppHandle, enable: bool) -> Result<(), EchoError> {
+    let autolaunch = app.autolaunch();
+    if enable {
+        autolaunch.enable()
+    } else {
+        autolaunch.disable()
+    }
+    .map_err(|e| format!("failed to update autostart: {}", e))?;
+    log::info!("Autostart {}", if enable { "enabled" } else { "disabled" });
+
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    store.set(PROMPTED_KEY, true);
+    store.save().map_err(|e| e.to_string().into())
+}
+
+#[tauri::command]
+pub fn is_autostart_enabled(app: AppHandle) -> Result<bool, EchoError> {
+    app.autolaunch()
+        .is_enabled()
+        .map_err(|e| format!("failed to read autostart state: {}", e).into())
+}
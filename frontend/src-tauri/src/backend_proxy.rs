@@ -0,0 +1,943 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, Manager, Runtime, State};
+use tauri_plugin_store::StoreExt;
+use tokio::sync::Mutex;
+use tokio_tungstenite::Connector;
+use uuid::Uuid;
+
+use crate::clipboard::ImportResult;
+use crate::command_queue;
+use crate::connections::ConnectionInfo;
+use crate::error::EchoError;
+use crate::migration::MigrationResult;
+use crate::port::PortState;
+use crate::STORE_FILE;
+
+/// Store key for the URL set with `set_remote_backend`.
+const REMOTE_BACKEND_KEY: &str = "remote_backend_url";
+/// Store key for the `ProxyConfig` set with `set_proxy`.
+const PROXY_CONFIG_KEY: &str = "proxy_config";
+/// Always reached directly, so a proxy never sits between the app and its sidecar.
+const LOCAL_HOSTS: &str = "127.0.0.1,localhost,::1";
+
+/// Replaces the short client timeout, which covers the whole body and would
+/// otherwise cut progress streams off after a couple of seconds.
+const STREAM_TIMEOUT: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Round trips `ping_backend` takes the median of.
+const PING_SAMPLES: u32 = 5;
+
+/// HTTP client shared by everything that talks to the backend, tuned for localhost.
+pub struct BackendClient {
+    /// Replaced by `set_proxy`.
+    client: RwLock<reqwest::Client>,
+    scheme: &'static str,
+    /// PEM of the certificate the backend's TLS certificate is issued by.
+    /// Replaced when the local CA is regenerated.
+    root_cert: RwLock<Option<Vec<u8>>>,
+    /// Base URL of a backend elsewhere that frontend requests go to instead,
+    /// e.g. on mobile where no sidecar can run.
+    remote: RwLock<Option<String>>,
+}
+
+/// Proxies for requests to a remote backend, for networks that only allow
+/// traffic through one. Without any, the system's proxy settings apply.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct ProxyConfig {
+    pub http_proxy: Option<String>,
+    pub https_proxy: Option<String>,
+    /// Hosts reached directly, e.g. `internal.example.com` or `.example.com`.
+    #[serde(default)]
+    pub no_proxy: Vec<String>,
+}
+
+impl ProxyConfig {
+    /// The config saved by `set_proxy`, or none.
+    pub fn load(app: &AppHandle) -> Self {
+        app.store(STORE_FILE)
+            .ok()
+            .and_then(|store| store.get(PROXY_CONFIG_KEY))
+            .and_then(|value| serde_json::from_value(value).ok())
+            .unwrap_or_default()
+    }
+
+    fn proxies(&self) -> Result<Vec<reqwest::Proxy>, EchoError> {
+        let no_proxy = reqwest::NoProxy::from_string(
+            &std::iter::once(LOCAL_HOSTS)
+                .chain(self.no_proxy.iter().map(String::as_str))
+                .collect::<Vec<_>>()
+                .join(","),
+        );
+        let proxy = |url: &str, build: fn(&str) -> reqwest::Result<reqwest::Proxy>| {
+            let parsed = tauri::Url::parse(url)
+                .map_err(|e| EchoError::invalid(format!("invalid proxy URL {}: {}", url, e)))?;
+            if !matches!(parsed.scheme(), "http" | "https") {
+                return Err(EchoError::invalid(format!(
+                    "proxy URL {} must be http or https",
+                    url
+                )));
+            }
+            let proxy = build(url)
+                .map_err(|e| EchoError::invalid(format!("invalid proxy URL {}: {}", url, e)))?;
+            Ok(proxy.no_proxy(no_proxy.clone()))
+        };
+        let mut proxies = Vec::new();
+        if let Some(url) = self.http_proxy.as_deref().filter(|url| !url.is_empty()) {
+            proxies.push(proxy(url, |url| reqwest::Proxy::http(url))?);
+        }
+        if let Some(url) = self.https_proxy.as_deref().filter(|url| !url.is_empty()) {
+            proxies.push(proxy(url, |url| reqwest::Proxy::https(url))?);
+        }
+        Ok(proxies)
+    }
+}
+
+fn build_client(
+    root_cert: Option<&[u8]>,
+    proxy: &ProxyConfig,
+) -> Result<reqwest::Client, EchoError> {
+    let mut builder = reqwest::Client::builder()
+        .connect_timeout(Duration::from_secs(1))
+        .timeout(Duration::from_secs(2))
+        .tcp_nodelay(true);
+    if let Some(pem) = root_cert {
+        builder = builder
+            .add_root_certificate(
+                reqwest::Certificate::from_pem(pem)
+                    .map_err(|e| format!("invalid backend certificate: {}", e))?,
+            )
+            .https_only(true);
+    }
+    for proxy in proxy.proxies()? {
+        builder = builder.proxy(proxy);
+    }
+    builder
+        .build()
+        .map_err(|e| format!("failed to build HTTP client: {}", e).into())
+}
+
+impl BackendClient {
+    /// With a `root_cert`, talk to the backend over HTTPS and trust that certificate.
+    pub fn new(root_cert: Option<&[u8]>, proxy: &ProxyConfig) -> Result<Self, EchoError> {
+        Ok(Self {
+            client: RwLock::new(build_client(root_cert, proxy)?),
+            scheme: if root_cert.is_some() { "https" } else { "http" },
+            root_cert: RwLock::new(root_cert.map(<[u8]>::to_vec)),
+            remote: RwLock::new(None),
+        })
+    }
+
+    /// URL of the WebSocket endpoint `path` of the backend on `port`, with the
+    /// connector to use for it when the backend runs with TLS.
+    pub fn ws(&self, port: u16, path: &str) -> Result<(String, Option<Connector>), EchoError> {
+        let Some(pem) = self.root_cert.read().unwrap().clone() else {
+            return Ok((format!("ws://127.0.0.1:{}{}", port, path), None));
+        };
+        let connector = native_tls::Certificate::from_pem(&pem)
+            .and_then(|cert| {
+                native_tls::TlsConnector::builder()
+                    .add_root_certificate(cert)
+                    .build()
+            })
+            .map_err(|e| format!("failed to set up TLS: {}", e))?;
+        Ok((
+            format!("wss://127.0.0.1:{}{}", port, path),
+            Some(Connector::NativeTls(connector)),
+        ))
+    }
+
+    /// Calls to the backend at `base_url`.
+    fn at(&self, base_url: String) -> BackendProxy {
+        BackendProxy {
+            transport: HttpTransport {
+                client: self.client.read().unwrap().clone(),
+                base_url,
+            },
+        }
+    }
+
+    /// Calls to the backend listening on `port`.
+    pub fn proxy(&self, port: u16) -> BackendProxy {
+        self.at(format!("{}://127.0.0.1:{}", self.scheme, port))
+    }
+
+    /// The remote backend if one is set, otherwise the local one on `port`.
+    pub fn current(&self, port: Option<u16>) -> Result<BackendProxy, EchoError> {
+        if let Some(base_url) = self.remote.read().unwrap().clone() {
+            return Ok(self.at(base_url));
+        }
+        port.map(|port| self.proxy(port))
+            .ok_or(EchoError::SidecarNotRunning)
+    }
+
+    /// Pick up the remote backend saved by `set_remote_backend`, if any.
+    pub fn restore_remote(&self, app: &AppHandle) {
+        let url = app
+            .store(STORE_FILE)
+            .ok()
+            .and_then(|store| store.get(REMOTE_BACKEND_KEY))
+            .and_then(|value| value.as_str().map(String::from));
+        if let Some(url) = &url {
+            log::info!("Using remote backend {}", url);
+        }
+        *self.remote.write().unwrap() = url;
+    }
+
+    /// Trust `pem` from now on, after the local CA was regenerated. Takes
+    /// effect with the next `reload`.
+    pub fn set_root_cert(&self, pem: Vec<u8>) {
+        *self.root_cert.write().unwrap() = Some(pem);
+    }
+
+    /// Rebuild the client from the saved proxy settings and pick up the saved
+    /// remote backend, after the store was cleared or replaced.
+    pub fn reload(&self, app: &AppHandle) -> Result<(), EchoError> {
+        let rebuilt = build_client(
+            self.root_cert.read().unwrap().as_deref(),
+            &ProxyConfig::load(app),
+        )?;
+        *self.client.write().unwrap() = rebuilt;
+        self.restore_remote(app);
+        Ok(())
+    }
+}
+
+/// How `BackendProxy` reaches the backend: `HttpTransport` in the app, and
+/// `MockTransport` in tests so they don't need a running backend. Only failing
+/// to get any response is an error.
+pub trait Transport {
+    /// Full URL of `path` on the backend, for error messages.
+    fn url(&self, path: &str) -> String;
+
+    fn request(
+        &self,
+        req: ProxyRequest,
+    ) -> impl Future<Output = Result<ProxyResponse, EchoError>> + Send;
+}
+
+/// The backend at `base_url`, over the shared client.
+pub struct HttpTransport {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl Transport for HttpTransport {
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    async fn request(&self, req: ProxyRequest) -> Result<ProxyResponse, EchoError> {
+        let method = reqwest::Method::from_bytes(req.method.to_uppercase().as_bytes())
+            .map_err(|_| EchoError::invalid(format!("invalid method {}", req.method)))?;
+        let mut request = self.client.request(method, self.url(&req.path));
+        for (name, value) in &req.headers {
+            request = request.header(name, value);
+        }
+        if let Some(body) = req.body {
+            request = request.body(body);
+        }
+
+        let resp = request.send().await.map_err(EchoError::backend)?;
+        let status = resp.status().as_u16();
+        let headers = resp
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+        let body = resp.text().await.map_err(EchoError::backend)?;
+        Ok(ProxyResponse {
+            status,
+            body,
+            headers,
+        })
+    }
+}
+
+/// Typed wrappers around the backend's HTTP API. Cheap to create; the
+/// underlying client is shared.
+pub struct BackendProxy<T = HttpTransport> {
+    transport: T,
+}
+
+fn request(method: &str, path: &str) -> ProxyRequest {
+    ProxyRequest {
+        method: method.to_string(),
+        path: path.to_string(),
+        body: None,
+        headers: HashMap::new(),
+    }
+}
+
+fn json_request(
+    method: &str,
+    path: &str,
+    body: &serde_json::Value,
+) -> Result<ProxyRequest, EchoError> {
+    let mut req = request(method, path);
+    req.body = Some(serde_json::to_string(body)?);
+    req.headers
+        .insert("Content-Type".to_string(), "application/json".to_string());
+    Ok(req)
+}
+
+fn success(url: String, resp: ProxyResponse) -> Result<ProxyResponse, EchoError> {
+    if !(200..300).contains(&resp.status) {
+        return Err(EchoError::BackendFailed {
+            url,
+            source: format!("status {}", resp.status),
+        });
+    }
+    Ok(resp)
+}
+
+impl<T: Transport> BackendProxy<T> {
+    #[cfg(test)]
+    pub fn new(transport: T) -> Self {
+        Self { transport }
+    }
+
+    #[cfg(test)]
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Send `req`, failing unless the backend answers with a success status.
+    async fn send(&self, req: ProxyRequest) -> Result<ProxyResponse, EchoError> {
+        let url = self.transport.url(&req.path);
+        let resp = self.transport.request(req).await?;
+        success(url, resp)
+    }
+
+    /// `Ok` if `GET /health` answers with a success status.
+    pub async fn health(&self) -> Result<(), EchoError> {
+        self.send(request("GET", "/health")).await?;
+        Ok(())
+    }
+
+    /// `Ok` if `GET /ping` answers with a success status.
+    pub async fn ping(&self) -> Result<(), EchoError> {
+        self.send(request("GET", "/ping")).await?;
+        Ok(())
+    }
+
+    /// Ask the backend to exit on its own.
+    pub async fn shutdown(&self) -> Result<(), EchoError> {
+        self.send(request("POST", "/shutdown")).await?;
+        Ok(())
+    }
+
+    /// Read `{"version": "x.y.z"}` from `GET /version`.
+    pub async fn version(&self) -> Result<String, EchoError> {
+        #[derive(Deserialize)]
+        struct VersionResponse {
+            version: String,
+        }
+
+        let resp = self.send(request("GET", "/version")).await?;
+        let body: VersionResponse = serde_json::from_str(&resp.body)?;
+        Ok(body.version)
+    }
+
+    /// Tell the backend the config file at `path` changed, as
+    /// `POST /reload {"path": ...}`. `backend/main.py` has no such route yet,
+    /// so every edit ends in `config-reload-failed` until it gets one.
+    pub async fn reload(&self, path: &Path) -> Result<(), EchoError> {
+        self.send(json_request("POST", "/reload", &json!({ "path": path }))?)
+            .await?;
+        Ok(())
+    }
+
+    /// Ask the backend to migrate its data written by app version `from` to `to`.
+    /// `None` if the backend has no `/migrate`, i.e. nothing to migrate.
+    pub async fn migrate(
+        &self,
+        from: Option<&str>,
+        to: &str,
+    ) -> Result<Option<MigrationResult>, EchoError> {
+        let url = self.transport.url("/migrate");
+        let req = json_request("POST", "/migrate", &json!({ "from": from, "to": to }))?;
+        let resp = self.transport.request(req).await?;
+        if resp.status == 404 {
+            return Ok(None);
+        }
+        let resp = success(url, resp)?;
+        Ok(Some(serde_json::from_str(&resp.body)?))
+    }
+
+    /// Trigger the scheduled task `action` with `POST /tasks/{action}`, its
+    /// payload as the JSON body. Which actions exist is up to the backend;
+    /// `backend/main.py` doesn't define any yet, so runs are logged as failed.
+    pub async fn run_task(
+        &self,
+        action: &str,
+        payload: &serde_json::Value,
+    ) -> Result<(), EchoError> {
+        self.send(json_request(
+            "POST",
+            &format!("/tasks/{}", action),
+            payload,
+        )?)
+        .await?;
+        Ok(())
+    }
+
+    /// The backend's open external connections, from `GET /connections`, which
+    /// answers with a JSON array of `ConnectionInfo`. `backend/main.py` doesn't
+    /// serve it yet, so until it does this fails with `BackendFailed`.
+    pub async fn connections(&self) -> Result<Vec<ConnectionInfo>, EchoError> {
+        let resp = self.send(request("GET", "/connections")).await?;
+        Ok(serde_json::from_str(&resp.body)?)
+    }
+
+    /// Turn the backend's power-save mode on or off with
+    /// `POST /power-save?enabled=<bool>`; any success status will do. Not yet
+    /// implemented by `backend/main.py`, which answers 404.
+    pub async fn set_power_save(&self, enabled: bool) -> Result<(), EchoError> {
+        self.send(request("POST", &format!("/power-save?enabled={}", enabled)))
+            .await?;
+        Ok(())
+    }
+
+    /// Hand files to the backend with `POST /import {"paths": [...]}`,
+    /// returning how many were sent. The backend is expected to read the files
+    /// itself; `backend/main.py` only has the upload-based `/convert/file` so
+    /// far, so drops are reported through `import-error` for now.
+    pub async fn import(&self, paths: &[PathBuf]) -> Result<usize, EchoError> {
+        let paths: Vec<_> = paths.iter().map(|p| p.to_string_lossy()).collect();
+        self.send(json_request("POST", "/import", &json!({ "paths": paths }))?)
+            .await?;
+        Ok(paths.len())
+    }
+
+    /// Import pasted text with `POST /import/clipboard {"text": ...}`, returning
+    /// the `ImportResult` the backend answers with. Missing from
+    /// `backend/main.py` for now, so this fails with `BackendFailed`.
+    pub async fn import_clipboard(&self, text: &str) -> Result<ImportResult, EchoError> {
+        let req = json_request("POST", "/import/clipboard", &json!({ "text": text }))?;
+        let resp = self.send(req).await?;
+        Ok(serde_json::from_str(&resp.body)?)
+    }
+
+    /// Send an arbitrary request on behalf of the frontend, returning non-2xx
+    /// responses as-is. The outer error means `req` was rejected without
+    /// sending anything, the inner one that the request itself failed.
+    pub async fn forward(
+        &self,
+        req: &ProxyRequest,
+    ) -> Result<Result<ProxyResponse, EchoError>, EchoError> {
+        if !req.path.starts_with('/') {
+            return Err(EchoError::invalid(format!(
+                "path must start with '/': {}",
+                req.path
+            )));
+        }
+        reqwest::Method::from_bytes(req.method.to_uppercase().as_bytes())
+            .map_err(|_| EchoError::invalid(format!("invalid method {}", req.method)))?;
+
+        Ok(self.transport.request(req.clone()).await)
+    }
+}
+
+impl BackendProxy {
+    /// Open the `text/event-stream` of progress updates for `operation_id` at
+    /// `GET /progress/{operation_id}`, one JSON or text `data:` message per
+    /// update. Streamed, so only over HTTP and not through `Transport`.
+    /// `backend/main.py` has no such route yet, so subscribing fails for now.
+    pub async fn progress(&self, operation_id: &str) -> reqwest::Result<reqwest::Response> {
+        self.transport
+            .client
+            .get(self.transport.url(&format!("/progress/{}", operation_id)))
+            .header("Accept", "text/event-stream")
+            .timeout(STREAM_TIMEOUT)
+            .send()
+            .await?
+            .error_for_status()
+    }
+}
+
+#[derive(Clone, Deserialize)]
+pub struct ProxyRequest {
+    pub method: String,
+    /// Path on the backend, including any query string; must start with `/`.
+    pub path: String,
+    pub body: Option<String>,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ProxyResponse {
+    pub status: u16,
+    pub body: String,
+    pub headers: HashMap<String, String>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum ProxyOutcome {
+    Response(ProxyResponse),
+    /// The backend couldn't be reached; the request is sent once it is back
+    /// and its result delivered as a `queued-command-result` event.
+    Queued {
+        id: Uuid,
+    },
+}
+
+/// Call the backend without the frontend needing its port or CORS. Non-2xx
+/// responses are returned as-is; only transport failures are errors.
+#[tauri::command]
+pub async fn proxy_request(
+    req: ProxyRequest,
+    handle: AppHandle,
+    client: State<'_, BackendClient>,
+) -> Result<ProxyOutcome, EchoError> {
+    let port = handle.state::<Mutex<PortState>>().lock().await.0;
+    forward_or_queue(&handle, client.current(port), req).await
+}
+
+/// Send `req` through `backend`, or hold it in the command queue if there is
+/// no backend to send it to right now.
+async fn forward_or_queue<R: Runtime>(
+    handle: &AppHandle<R>,
+    backend: Result<BackendProxy<impl Transport>, EchoError>,
+    req: ProxyRequest,
+) -> Result<ProxyOutcome, EchoError> {
+    let backend = match backend {
+        Ok(backend) => backend,
+        Err(e) => return command_queue::enqueue(handle, req, e).await,
+    };
+    match backend.forward(&req).await? {
+        Ok(resp) => Ok(ProxyOutcome::Response(resp)),
+        // Nothing reached the backend, so it is safe to send again later
+        Err(e @ EchoError::BackendUnreachable { .. }) => {
+            command_queue::enqueue(handle, req, e).await
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Time `iterations` sequential `GET /ping` round trips, in microseconds.
+async fn sample_latency(
+    handle: &AppHandle,
+    client: &BackendClient,
+    iterations: u32,
+) -> Result<Vec<u64>, EchoError> {
+    let port = handle.state::<Mutex<PortState>>().lock().await.0;
+    let backend = client.current(port)?;
+
+    let mut samples = Vec::with_capacity(iterations as usize);
+    for _ in 0..iterations {
+        let start = Instant::now();
+        backend.ping().await?;
+        samples.push(start.elapsed().as_micros() as u64);
+    }
+    Ok(samples)
+}
+
+/// Median round-trip latency to the backend in microseconds.
+#[tauri::command]
+pub async fn ping_backend(
+    handle: AppHandle,
+    client: State<'_, BackendClient>,
+) -> Result<u64, EchoError> {
+    let mut samples = sample_latency(&handle, &client, PING_SAMPLES).await?;
+    samples.sort_unstable();
+    Ok(samples[samples.len() / 2])
+}
+
+/// Every round-trip latency from `iterations` pings, in microseconds, for
+/// plotting as a histogram.
+#[tauri::command]
+pub async fn benchmark_backend(
+    iterations: u32,
+    handle: AppHandle,
+    client: State<'_, BackendClient>,
+) -> Result<Vec<u64>, EchoError> {
+    sample_latency(&handle, &client, iterations).await
+}
+
+/// Send frontend requests to the backend at `url` (e.g. `https://echo.example.com`)
+/// instead of the local sidecar; an empty `url` goes back to the sidecar.
+/// Remembered across launches.
+#[tauri::command]
+pub fn set_remote_backend(
+    url: String,
+    app: AppHandle,
+    client: State<'_, BackendClient>,
+) -> Result<(), EchoError> {
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    let remote = if url.is_empty() {
+        store.delete(REMOTE_BACKEND_KEY);
+        None
+    } else {
+        let parsed = tauri::Url::parse(&url)
+            .map_err(|e| EchoError::invalid(format!("invalid URL: {}", e)))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(EchoError::invalid(
+                "remote backend URL must be http or https",
+            ));
+        }
+        let base_url = parsed.as_str().trim_end_matches('/').to_string();
+        store.set(REMOTE_BACKEND_KEY, base_url.as_str());
+        Some(base_url)
+    };
+    store
+        .save()
+        .map_err(|e| format!("failed to save remote backend: {}", e))?;
+
+    match &remote {
+        Some(url) => log::info!("Using remote backend {}", url),
+        None => log::info!("Using the local backend"),
+    }
+    *client.remote.write().unwrap() = remote;
+    Ok(())
+}
+
+/// Send backend requests through the proxies in `config`, remembered across
+/// launches. Requests already in flight finish on the old connection.
+#[tauri::command]
+pub fn set_proxy(
+    config: ProxyConfig,
+    app: AppHandle,
+    client: State<'_, BackendClient>,
+) -> Result<(), EchoError> {
+    let rebuilt = build_client(client.root_cert.read().unwrap().as_deref(), &config)?;
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    store.set(PROXY_CONFIG_KEY, serde_json::to_value(&config)?);
+    store
+        .save()
+        .map_err(|e| format!("failed to save proxy settings: {}", e))?;
+    *client.client.write().unwrap() = rebuilt;
+    log::info!("Updated backend proxy settings");
+    Ok(())
+}
+
+/// Whole of `test_backend_connection`, from DNS to the health response.
+const CONNECTION_TEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Serialize)]
+pub struct ConnectionTestResult {
+    pub dns_ms: u64,
+    pub tcp_ms: u64,
+    pub http_ms: u64,
+    pub status_code: u16,
+    pub tls: bool,
+}
+
+/// Loopback and link-local addresses, which a remote backend has no reason
+/// to be on but which would let a URL probe the machine or its cloud metadata.
+fn is_local(ip: IpAddr) -> bool {
+    match ip.to_canonical() {
+        IpAddr::V4(ip) => ip.is_loopback() || ip.is_link_local() || ip.is_unspecified(),
+        // fe80::/10; `is_unicast_link_local` is newer than the MSRV
+        IpAddr::V6(ip) => {
+            ip.is_loopback() || ip.is_unspecified() || (ip.segments()[0] & 0xffc0) == 0xfe80
+        }
+    }
+}
+
+/// Check that a remote backend at `url` is reachable before `set_remote_backend`,
+/// timing each step. A non-2xx health response is reported, not an error.
+#[tauri::command]
+pub async fn test_backend_connection(url: String) -> Result<ConnectionTestResult, EchoError> {
+    tokio::time::timeout(CONNECTION_TEST_TIMEOUT, test_connection(&url))
+        .await
+        .map_err(|_| format!("connecting to {} timed out", url))?
+}
+
+async fn test_connection(url: &str) -> Result<ConnectionTestResult, EchoError> {
+    let parsed =
+        tauri::Url::parse(url).map_err(|e| EchoError::invalid(format!("invalid URL: {}", e)))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(EchoError::invalid("backend URL must be http or https"));
+    }
+    let (Some(host), Some(port)) = (parsed.host_str(), parsed.port_or_known_default()) else {
+        return Err(EchoError::invalid("backend URL has no host"));
+    };
+    // `host_str` keeps the brackets of IPv6 literals
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+
+    let started = Instant::now();
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|e| format!("failed to resolve {}: {}", host, e))?
+        .collect();
+    let dns_ms = started.elapsed().as_millis() as u64;
+    if addrs.iter().any(|addr| is_local(addr.ip())) {
+        log::warn!("Refusing to test {}, it resolves to a local address", url);
+        return Err(EchoError::Unauthorized);
+    }
+    let addr = *addrs
+        .first()
+        .ok_or_else(|| format!("{} has no addresses", host))?;
+
+    let started = Instant::now();
+    tokio::net::TcpStream::connect(addr)
+        .await
+        .map_err(|e| format!("failed to connect to {}: {}", addr, e))?;
+    let tcp_ms = started.elapsed().as_millis() as u64;
+
+    // Pinned to the checked address, and without redirects, so neither a
+    // second lookup nor the server can point the request somewhere local
+    let client = reqwest::Client::builder()
+        .resolve(host, addr)
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .map_err(EchoError::backend)?;
+    // Under the URL's path, without its query or fragment
+    let mut health = parsed.clone();
+    health.set_path(&format!("{}/health", parsed.path().trim_end_matches('/')));
+    health.set_query(None);
+    health.set_fragment(None);
+    let started = Instant::now();
+    let resp = client
+        .get(health)
+        .send()
+        .await
+        .map_err(EchoError::backend)?;
+    let http_ms = started.elapsed().as_millis() as u64;
+
+    Ok(ConnectionTestResult {
+        dns_ms,
+        tcp_ms,
+        http_ms,
+        status_code: resp.status().as_u16(),
+        tls: parsed.scheme() == "https",
+    })
+}
+
+/// Answers with canned responses by method and path, and fails like a backend
+/// that isn't listening for anything else. Records every request it gets.
+#[cfg(test)]
+#[derive(Default)]
+pub struct MockTransport {
+    responses: HashMap<(String, String), (u16, String)>,
+    requests: std::sync::Mutex<Vec<ProxyRequest>>,
+}
+
+#[cfg(test)]
+impl MockTransport {
+    pub fn respond(mut self, method: &str, path: &str, status: u16, body: &str) -> Self {
+        self.responses.insert(
+            (method.to_string(), path.to_string()),
+            (status, body.to_string()),
+        );
+        self
+    }
+
+    pub fn requests(&self) -> Vec<ProxyRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+impl Transport for MockTransport {
+    fn url(&self, path: &str) -> String {
+        format!("http://127.0.0.1:8000{}", path)
+    }
+
+    async fn request(&self, req: ProxyRequest) -> Result<ProxyResponse, EchoError> {
+        let url = self.url(&req.path);
+        let response = self
+            .responses
+            .get(&(req.method.clone(), req.path.clone()))
+            .cloned();
+        self.requests.lock().unwrap().push(req);
+        let (status, body) = response.ok_or_else(|| EchoError::BackendUnreachable {
+            url,
+            source: "connection refused".to_string(),
+        })?;
+        Ok(ProxyResponse {
+            status,
+            body,
+            headers: HashMap::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_queue::{CommandQueue, CommandQueueConfig};
+
+    fn backend(transport: MockTransport) -> BackendProxy<MockTransport> {
+        BackendProxy::new(transport)
+    }
+
+    /// A mock app whose command queue holds up to `max_queue_size` requests.
+    fn app(max_queue_size: usize) -> tauri::App<tauri::test::MockRuntime> {
+        let app = tauri::test::mock_builder()
+            .build(tauri::test::mock_context(tauri::test::noop_assets()))
+            .unwrap();
+        app.manage(Mutex::new(CommandQueue::new(CommandQueueConfig {
+            max_queue_size,
+            ..Default::default()
+        })));
+        app
+    }
+
+    #[tokio::test]
+    async fn health_needs_a_success_status() {
+        let up = backend(MockTransport::default().respond("GET", "/health", 200, "{}"));
+        assert!(up.health().await.is_ok());
+
+        let failing = backend(MockTransport::default().respond("GET", "/health", 503, ""));
+        assert!(matches!(
+            failing.health().await,
+            Err(EchoError::BackendFailed { .. })
+        ));
+
+        let down = backend(MockTransport::default());
+        assert!(matches!(
+            down.health().await,
+            Err(EchoError::BackendUnreachable { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn version_is_read_from_the_body() {
+        let proxy = backend(MockTransport::default().respond(
+            "GET",
+            "/version",
+            200,
+            r#"{"version": "1.2.3"}"#,
+        ));
+        assert_eq!(proxy.version().await.unwrap(), "1.2.3");
+    }
+
+    #[tokio::test]
+    async fn migrate_without_endpoint_is_nothing_to_do() {
+        let proxy = backend(MockTransport::default().respond("POST", "/migrate", 404, ""));
+        assert!(proxy
+            .migrate(Some("1.0.0"), "1.1.0")
+            .await
+            .unwrap()
+            .is_none());
+
+        let failing = backend(MockTransport::default().respond("POST", "/migrate", 500, ""));
+        assert!(failing.migrate(Some("1.0.0"), "1.1.0").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn json_bodies_are_sent_with_content_type() {
+        let proxy = backend(MockTransport::default().respond("POST", "/import", 200, "{}"));
+        let sent = proxy.import(&[PathBuf::from("notes.txt")]).await.unwrap();
+        assert_eq!(sent, 1);
+
+        let sent = proxy.transport.requests();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(
+            sent[0].headers.get("Content-Type").map(String::as_str),
+            Some("application/json")
+        );
+        let body: serde_json::Value =
+            serde_json::from_str(sent[0].body.as_deref().unwrap()).unwrap();
+        assert_eq!(body, json!({ "paths": ["notes.txt"] }));
+    }
+
+    #[tokio::test]
+    async fn forward_returns_error_statuses_as_is() {
+        let proxy = backend(MockTransport::default().respond("GET", "/exams/list", 500, "boom"));
+        let req = request("GET", "/exams/list");
+        let resp = proxy.forward(&req).await.unwrap().unwrap();
+        assert_eq!(resp.status, 500);
+        assert_eq!(resp.body, "boom");
+    }
+
+    #[tokio::test]
+    async fn forward_rejects_bad_requests_without_sending() {
+        let proxy = backend(MockTransport::default());
+        assert!(proxy.forward(&request("GET", "exams/list")).await.is_err());
+        assert!(proxy
+            .forward(&request("NOT A METHOD", "/exams/list"))
+            .await
+            .is_err());
+        assert!(proxy.transport.requests().is_empty());
+    }
+
+    /// What `proxy_request` queues for later instead of failing.
+    #[tokio::test]
+    async fn forward_to_a_stopped_backend_is_unreachable() {
+        let proxy = backend(MockTransport::default());
+        let result = proxy.forward(&request("POST", "/settings")).await.unwrap();
+        assert!(matches!(result, Err(EchoError::BackendUnreachable { .. })));
+    }
+
+    #[test]
+    fn proxy_request_returns_responses() {
+        let app = app(1);
+        let up = backend(MockTransport::default().respond("GET", "/exams/list", 404, ""));
+        let outcome = tauri::async_runtime::block_on(forward_or_queue(
+            app.handle(),
+            Ok(up),
+            request("GET", "/exams/list"),
+        ))
+        .unwrap();
+        assert!(matches!(
+            outcome,
+            ProxyOutcome::Response(ProxyResponse { status: 404, .. })
+        ));
+    }
+
+    #[test]
+    fn proxy_request_queues_while_the_backend_is_down() {
+        let app = app(1);
+        let down = backend(MockTransport::default());
+        let outcome = tauri::async_runtime::block_on(forward_or_queue(
+            app.handle(),
+            Ok(down),
+            request("POST", "/settings"),
+        ))
+        .unwrap();
+        assert!(matches!(outcome, ProxyOutcome::Queued { .. }));
+
+        // With the queue full it fails as it would without one
+        let down = backend(MockTransport::default());
+        let result = tauri::async_runtime::block_on(forward_or_queue(
+            app.handle(),
+            Ok(down),
+            request("POST", "/settings"),
+        ));
+        assert!(matches!(result, Err(EchoError::BackendUnreachable { .. })));
+    }
+
+    #[test]
+    fn proxy_request_queues_until_the_sidecar_runs() {
+        let app = app(1);
+        let outcome = tauri::async_runtime::block_on(forward_or_queue(
+            app.handle(),
+            Err::<BackendProxy<MockTransport>, _>(EchoError::SidecarNotRunning),
+            request("POST", "/settings"),
+        ))
+        .unwrap();
+        assert!(matches!(outcome, ProxyOutcome::Queued { .. }));
+    }
+
+    #[test]
+    fn proxy_request_does_not_queue_bad_requests() {
+        let app = app(1);
+        let result = tauri::async_runtime::block_on(forward_or_queue(
+            app.handle(),
+            Ok(backend(MockTransport::default())),
+            request("POST", "settings"),
+        ));
+        assert!(matches!(result, Err(EchoError::InvalidInput { .. })));
+
+        // The queue is still empty
+        let outcome = tauri::async_runtime::block_on(forward_or_queue(
+            app.handle(),
+            Ok(backend(MockTransport::default())),
+            request("POST", "/settings"),
+        ))
+        .unwrap();
+        assert!(matches!(outcome, ProxyOutcome::Queued { .. }));
+    }
+}
@@ -0,0 +1,75 @@
+//! Plain-text clipboard access that doesn't depend on a user gesture like the
+//! browser clipboard API does.
+//!
+//! macOS and Windows need no extra permissions. On Linux a running X11 or
+//! Wayland session is required (Wayland compositors must support the
+//! data-control protocol), and under X11 text written by the app is only
+//! available while the app is running.
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tokio::sync::Mutex;
+
+use crate::backend_proxy::BackendClient;
+use crate::error::EchoError;
+use crate::port::PortState;
+
+/// The item the backend created from imported clipboard text.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ImportResult {
+    pub id: String,
+    pub preview: String,
+}
+
+#[tauri::command]
+pub fn read_clipboard<R: Runtime>(app: AppHandle<R>) -> Result<String, EchoError> {
+    app.clipboard()
+        .read_text()
+        .map_err(|e| format!("failed to read clipboard: {}", e).into())
+}
+
+#[tauri::command]
+pub fn write_clipboard<R: Runtime>(app: AppHandle<R>, text: String) -> Result<(), EchoError> {
+    app.clipboard()
+        .write_text(text)
+        .map_err(|e| format!("failed to write clipboard: {}", e).into())
+}
+
+/// Send the clipboard text to the backend's `/import/clipboard` and emit
+/// `import-completed` with the new item.
+#[tauri::command]
+pub async fn import_from_clipboard(app: AppHandle) -> Result<ImportResult, EchoError> {
+    let text = app
+        .clipboard()
+        .read_text()
+        .ok()
+        .filter(|text| !text.is_empty())
+        .ok_or_else(|| EchoError::invalid("the clipboard is empty or doesn't hold text"))?;
+    let port = app.state::<Mutex<PortState>>().lock().await.0;
+    let result = app
+        .state::<BackendClient>()
+        .current(port)?
+        .import_clipboard(&text)
+        .await?;
+    let _ = app.emit("import-completed", result.clone());
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[ignore = "needs a desktop session with a clipboard"]
+    fn clipboard_round_trip() {
+        let app = tauri::test::mock_builder()
+            .plugin(tauri_plugin_clipboard_manager::init())
+            .build(tauri::test::mock_context(tauri::test::noop_assets()))
+            .unwrap();
+
+        let text = "echo clipboard test ✓";
+        write_clipboard(app.handle().clone(), text.to_string()).unwrap();
+        assert_eq!(read_clipboard(app.handle().clone()).unwrap(), text);
+    }
+}
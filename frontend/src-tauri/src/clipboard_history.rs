@@ -0,0 +1,126 @@
+//! Recently copied text, recorded while the app runs. Only built with the
+//! `clipboard_history` feature.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_store::StoreExt;
+use uuid::Uuid;
+
+use crate::error::EchoError;
+use crate::logs::now_ms;
+use crate::STORE_FILE;
+
+const CLIPBOARD_HISTORY_KEY: &str = "clipboard_history";
+const MAX_ENTRIES: usize = 50;
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ClipboardEntry {
+    pub id: Uuid,
+    pub content: String,
+    /// Milliseconds since the Unix epoch.
+    pub copied_at: u64,
+}
+
+/// Copied text, newest first, mirrored to the store.
+pub struct ClipboardHistory(Mutex<VecDeque<ClipboardEntry>>);
+
+impl ClipboardHistory {
+    pub fn load(app: &AppHandle) -> Self {
+        let entries = app
+            .store(STORE_FILE)
+            .ok()
+            .and_then(|store| store.get(CLIPBOARD_HISTORY_KEY))
+            .and_then(|value| serde_json::from_value(value).ok())
+            .unwrap_or_default();
+        Self(Mutex::new(entries))
+    }
+
+    /// Read the history from the store again, after it was cleared or replaced.
+    pub fn reload(&self, app: &AppHandle) {
+        *self.0.lock().unwrap() = Self::load(app).0.into_inner().unwrap();
+    }
+
+    /// Put `content` at the top, moving it there if it is already listed.
+    fn record(&self, app: &AppHandle, content: String) -> Result<(), EchoError> {
+        let mut entries = self.0.lock().unwrap();
+        entries.retain(|entry| entry.content != content);
+        entries.push_front(ClipboardEntry {
+            id: Uuid::new_v4(),
+            content,
+            copied_at: now_ms(),
+        });
+        entries.truncate(MAX_ENTRIES);
+        persist(app, &entries)
+    }
+}
+
+fn persist(app: &AppHandle, entries: &VecDeque<ClipboardEntry>) -> Result<(), EchoError> {
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    store.set(CLIPBOARD_HISTORY_KEY, serde_json::to_value(entries)?);
+    store
+        .save()
+        .map_err(|e| format!("failed to save clipboard history: {}", e).into())
+}
+
+fn hash(content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Poll the clipboard every `POLL_INTERVAL` and record text that changed.
+pub fn start_watcher(handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut last = None;
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            interval.tick().await;
+            // Fails when the clipboard is empty or holds something other than text
+            let Ok(content) = handle.clipboard().read_text() else {
+                continue;
+            };
+            let current = hash(&content);
+            if content.is_empty() || last == Some(current) {
+                continue;
+            }
+            last = Some(current);
+            if let Err(e) = handle.state::<ClipboardHistory>().record(&handle, content) {
+                log::warn!("Failed to record clipboard entry: {}", e);
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_clipboard_history(history: State<'_, ClipboardHistory>) -> Vec<ClipboardEntry> {
+    history.0.lock().unwrap().iter().cloned().collect()
+}
+
+#[tauri::command]
+pub fn clear_clipboard_history(
+    app: AppHandle,
+    history: State<'_, ClipboardHistory>,
+) -> Result<(), EchoError> {
+    let mut entries = history.0.lock().unwrap();
+    entries.clear();
+    persist(&app, &entries)
+}
+
+#[tauri::command]
+pub fn delete_clipboard_entry(
+    id: Uuid,
+    app: AppHandle,
+    history: State<'_, ClipboardHistory>,
+) -> Result<(), EchoError> {
+    let mut entries = history.0.lock().unwrap();
+    entries.retain(|entry| entry.id != id);
+    persist(&app, &entries)
+}
@@ -0,0 +1,162 @@
+//! Requests from `proxy_request` that arrive while the backend is down, e.g.
+//! during a restart, are held here and sent once it is ready again.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::backend_proxy::{BackendClient, ProxyOutcome, ProxyRequest, ProxyResponse};
+use crate::error::EchoError;
+use crate::port::PortState;
+
+/// Limits on held requests.
+///
+/// Read from `plugins.backend` in `tauri.conf.json` when present.
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct CommandQueueConfig {
+    /// Requests beyond this many fail right away as they would without the queue.
+    pub max_queue_size: usize,
+    /// Requests held longer than this are given up on.
+    pub max_age_secs: u64,
+}
+
+impl Default for CommandQueueConfig {
+    fn default() -> Self {
+        Self {
+            max_queue_size: 50,
+            max_age_secs: 30,
+        }
+    }
+}
+
+struct QueuedCommand {
+    id: Uuid,
+    req: ProxyRequest,
+    queued_at: Instant,
+}
+
+pub struct CommandQueue {
+    commands: VecDeque<QueuedCommand>,
+    config: CommandQueueConfig,
+}
+
+impl CommandQueue {
+    pub fn new(config: CommandQueueConfig) -> Self {
+        Self {
+            commands: VecDeque::new(),
+            config,
+        }
+    }
+
+    /// Remove the commands held longer than `max_age_secs`, oldest first.
+    fn take_expired(&mut self) -> Vec<QueuedCommand> {
+        let max_age = Duration::from_secs(self.config.max_age_secs);
+        let live = self
+            .commands
+            .iter()
+            .position(|command| command.queued_at.elapsed() < max_age)
+            .unwrap_or(self.commands.len());
+        self.commands.drain(..live).collect()
+    }
+}
+
+/// Sent for every queued request once it has been sent or given up on.
+#[derive(Clone, Serialize)]
+struct QueuedCommandResult {
+    id: Uuid,
+    response: Option<ProxyResponse>,
+    error: Option<EchoError>,
+}
+
+impl QueuedCommandResult {
+    fn emit<R: Runtime>(self, handle: &AppHandle<R>) {
+        let _ = handle.emit("queued-command-result", self);
+    }
+}
+
+fn emit_expired<R: Runtime>(handle: &AppHandle<R>, expired: Vec<QueuedCommand>) {
+    for command in expired {
+        log::warn!("Dropping request {} queued for too long", command.id);
+        QueuedCommandResult {
+            id: command.id,
+            response: None,
+            error: Some("backend was unavailable for too long".into()),
+        }
+        .emit(handle);
+    }
+}
+
+/// Hold `req` until the backend is ready, or fail with `unavailable` if the
+/// queue is full.
+pub async fn enqueue<R: Runtime>(
+    handle: &AppHandle<R>,
+    req: ProxyRequest,
+    unavailable: EchoError,
+) -> Result<ProxyOutcome, EchoError> {
+    let state = handle.state::<Mutex<CommandQueue>>();
+    let mut queue = state.lock().await;
+    emit_expired(handle, queue.take_expired());
+    if queue.commands.len() >= queue.config.max_queue_size {
+        return Err(unavailable);
+    }
+
+    let id = Uuid::new_v4();
+    log::debug!(
+        "Backend unavailable, queuing {} {} as {}",
+        req.method,
+        req.path,
+        id
+    );
+    queue.commands.push_back(QueuedCommand {
+        id,
+        req,
+        queued_at: Instant::now(),
+    });
+    Ok(ProxyOutcome::Queued { id })
+}
+
+/// Send the held requests in the order they came in. Called on `backend-ready`.
+pub async fn replay(handle: AppHandle) {
+    let state = handle.state::<Mutex<CommandQueue>>();
+    loop {
+        let command = {
+            let mut queue = state.lock().await;
+            emit_expired(&handle, queue.take_expired());
+            match queue.commands.pop_front() {
+                Some(command) => command,
+                None => return,
+            }
+        };
+
+        let port = handle.state::<Mutex<PortState>>().lock().await.0;
+        let result = match handle.state::<BackendClient>().current(port) {
+            Ok(backend) => backend.forward(&command.req).await,
+            Err(e) => Err(e),
+        };
+        let (response, error) = match result {
+            Ok(Ok(resp)) => (Some(resp), None),
+            // Down again: keep the rest for the next `backend-ready`
+            Ok(Err(EchoError::BackendUnreachable { .. })) => {
+                state.lock().await.commands.push_front(command);
+                return;
+            }
+            Ok(Err(e)) => (None, Some(e)),
+            Err(EchoError::SidecarNotRunning) => {
+                state.lock().await.commands.push_front(command);
+                return;
+            }
+            Err(e) => (None, Some(e)),
+        };
+        QueuedCommandResult {
+            id: command.id,
+            response,
+            error,
+        }
+        .emit(&handle);
+    }
+}
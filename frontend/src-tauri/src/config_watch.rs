@@ -0,0 +1,101 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::{mpsc, Mutex};
+use tokio_util::sync::CancellationToken;
+
+use crate::backend_proxy::BackendClient;
+use crate::port::PortState;
+
+/// Editors often write a file several times in a row when saving.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Keeps the config file watcher alive for the lifetime of the app.
+pub struct WatcherState {
+    _watcher: RecommendedWatcher,
+}
+
+#[derive(Clone, Serialize)]
+struct ConfigReloadFailed {
+    error: String,
+}
+
+/// `echo/config.toml` in the user config directory (`~/.config` on Linux).
+fn config_path(app: &AppHandle) -> tauri::Result<PathBuf> {
+    Ok(app.path().config_dir()?.join("echo").join("config.toml"))
+}
+
+/// Watch the user config file and ask the backend to reload it on change.
+pub fn start(app: &AppHandle) -> Result<WatcherState, Box<dyn std::error::Error>> {
+    let path = config_path(app)?;
+    let dir = path.parent().expect("config path has a parent");
+    std::fs::create_dir_all(dir)?;
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let watched = path.clone();
+    // Watch the directory rather than the file so editors that save by
+    // replacing the file don't end the watch
+    let mut watcher =
+        notify::recommended_watcher(move |res: notify::Result<notify::Event>| match res {
+            Ok(event)
+                if matches!(event.kind, EventKind::Modify(_) | EventKind::Create(_))
+                    && event.paths.iter().any(|p| p == &watched) =>
+            {
+                let _ = tx.send(());
+            }
+            Ok(_) => {}
+            Err(e) => log::warn!("Config watcher error: {}", e),
+        })?;
+    watcher.watch(dir, RecursiveMode::NonRecursive)?;
+    log::info!("Watching {} for changes", path.display());
+
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut pending: Option<CancellationToken> = None;
+        while rx.recv().await.is_some() {
+            let token = CancellationToken::new();
+            if let Some(old) = pending.replace(token.clone()) {
+                old.cancel();
+            }
+            let handle = handle.clone();
+            let path = path.clone();
+            tauri::async_runtime::spawn(async move {
+                tokio::select! {
+                    _ = token.cancelled() => {}
+                    _ = tokio::time::sleep(DEBOUNCE) => reload(&handle, &path).await,
+                }
+            });
+        }
+    });
+
+    Ok(WatcherState { _watcher: watcher })
+}
+
+async fn reload(app: &AppHandle, path: &Path) {
+    let Some(port) = app.state::<Mutex<PortState>>().lock().await.0 else {
+        log::warn!("Config changed but the backend has no port yet");
+        return;
+    };
+    log::info!(
+        "Config changed, asking backend to reload {}",
+        path.display()
+    );
+
+    match app.state::<BackendClient>().proxy(port).reload(path).await {
+        Ok(_) => {
+            let _ = app.emit("config-reloaded", ());
+        }
+        Err(e) => {
+            log::warn!("Backend failed to reload config: {}", e);
+            let _ = app.emit(
+                "config-reload-failed",
+                ConfigReloadFailed {
+                    error: e.to_string(),
+                },
+            );
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::Mutex;
+
+use crate::backend_proxy::BackendClient;
+use crate::error::EchoError;
+use crate::port::PortState;
+
+/// How long a listing from the backend is reused, so a UI refreshing rapidly
+/// doesn't hit the backend on every refresh.
+const CACHE_TTL: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ConnectionInfo {
+    pub remote_addr: String,
+    pub protocol: String,
+    pub state: String,
+    /// Milliseconds since the Unix epoch.
+    pub opened_at: u64,
+}
+
+/// When a listing was taken, and the connections in it.
+type Snapshot = (Instant, Vec<ConnectionInfo>);
+
+#[derive(Default)]
+pub struct ConnectionCache(Mutex<Option<Snapshot>>);
+
+/// External connections the backend has open, from `GET /connections`. Emits
+/// `connections-changed` with the new list when the number of connections
+/// differs from the previous listing.
+#[tauri::command]
+pub async fn get_active_connections(
+    handle: AppHandle,
+    cache: State<'_, ConnectionCache>,
+) -> Result<Vec<ConnectionInfo>, EchoError> {
+    let mut cached = cache.0.lock().await;
+    if let Some((taken, connections)) = cached.as_ref() {
+        if taken.elapsed() < CACHE_TTL {
+            return Ok(connections.clone());
+        }
+    }
+
+    let port = handle.state::<Mutex<PortState>>().lock().await.0;
+    let connections = handle
+        .state::<BackendClient>()
+        .current(port)?
+        .connections()
+        .await?;
+    let changed = cached
+        .as_ref()
+        .is_some_and(|(_, previous)| previous.len() != connections.len());
+    if changed {
+        let _ = handle.emit("connections-changed", &connections);
+    }
+    *cached = Some((Instant::now(), connections.clone()));
+    Ok(connections)
+}
@@ -0,0 +1,65 @@
+//! Crash reporter shown when the backend exits abnormally. The `crash.html`
+//! page fetches the report with `get_crash_report` and offers to copy it, send
+//! it with `send_feedback`, restart the backend or quit.
+
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager, State, WebviewUrl, WebviewWindowBuilder};
+
+use crate::logs::{LogBuffer, LogEntry};
+
+const CRASH_WINDOW: &str = "crash";
+/// Backend log lines included in the report.
+pub const CRASH_LOG_LINES: usize = 50;
+
+#[derive(Clone, Serialize)]
+pub struct CrashReport {
+    pub exit_code: i32,
+    pub logs: Vec<LogEntry>,
+}
+
+/// The report for the most recent crash.
+#[derive(Default)]
+pub struct CrashReportState(Mutex<Option<CrashReport>>);
+
+impl CrashReportState {
+    pub fn clear(&self) {
+        *self.0.lock().unwrap() = None;
+    }
+}
+
+/// Open the crash reporter for an exit with `exit_code`, or bring it forward
+/// with the new report if it is already open.
+pub fn show_crash_report_dialog(exit_code: i32, last_logs: Vec<LogEntry>, handle: &AppHandle) {
+    *handle.state::<CrashReportState>().0.lock().unwrap() = Some(CrashReport {
+        exit_code,
+        logs: last_logs,
+    });
+
+    if let Some(window) = handle.get_webview_window(CRASH_WINDOW) {
+        // Reloading makes the page fetch the new report
+        let _ = window.reload();
+        let _ = window.set_focus();
+        return;
+    }
+    let built =
+        WebviewWindowBuilder::new(handle, CRASH_WINDOW, WebviewUrl::App("crash.html".into()))
+            .title("Echo — Backend Crashed")
+            .inner_size(640.0, 520.0)
+            .build();
+    if let Err(e) = built {
+        log::error!("Failed to open crash reporter: {}", e);
+    }
+}
+
+/// Called when the backend exits with a failure the app didn't ask for.
+pub fn on_backend_crash(handle: &AppHandle, exit_code: i32) {
+    let logs = handle.state::<LogBuffer>().recent(CRASH_LOG_LINES);
+    show_crash_report_dialog(exit_code, logs, handle);
+}
+
+#[tauri::command]
+pub fn get_crash_report(state: State<'_, CrashReportState>) -> Option<CrashReport> {
+    state.0.lock().unwrap().clone()
+}
@@ -0,0 +1,98 @@
+//! Encrypting user data at rest with AES-256-GCM. Keys live in the OS
+//! keychain (Keychain, Credential Manager or the Secret Service), are never
+//! logged and never leave this process; the frontend only sees their ids.
+//!
+//! Ciphertext is the 12-byte random nonce followed by the sealed data. The
+//! commands are async so a keychain unlock prompt doesn't block the main thread.
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use tauri::AppHandle;
+
+use crate::error::EchoError;
+
+const NONCE_LEN: usize = 12;
+
+fn entry(app: &AppHandle, key_id: &str) -> Result<keyring::Entry, EchoError> {
+    if key_id.is_empty() {
+        return Err(EchoError::invalid("key id must not be empty"));
+    }
+    keyring::Entry::new(&app.config().identifier, key_id)
+        .map_err(|e| format!("failed to open keychain: {}", e).into())
+}
+
+fn load_cipher(app: &AppHandle, key_id: &str) -> Result<Aes256Gcm, EchoError> {
+    let encoded = entry(app, key_id)?.get_password().map_err(|e| match e {
+        keyring::Error::NoEntry => EchoError::invalid(format!("no key {}", key_id)),
+        e => format!("failed to read key {}: {}", key_id, e).into(),
+    })?;
+    let key = STANDARD
+        .decode(encoded)
+        .ok()
+        .filter(|key| key.len() == 32)
+        .ok_or_else(|| format!("key {} in the keychain is corrupt", key_id))?;
+    Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)))
+}
+
+/// Create a random key under `key_id`. Fails if there already is one, since
+/// replacing it would make everything encrypted with it unreadable.
+#[tauri::command]
+pub async fn generate_key(key_id: String, app: AppHandle) -> Result<(), EchoError> {
+    let entry = entry(&app, &key_id)?;
+    match entry.get_password() {
+        Ok(_) => return Err(EchoError::invalid(format!("key {} already exists", key_id))),
+        Err(keyring::Error::NoEntry) => {}
+        Err(e) => return Err(format!("failed to read key {}: {}", key_id, e).into()),
+    }
+    let key = Aes256Gcm::generate_key(OsRng);
+    entry
+        .set_password(&STANDARD.encode(key))
+        .map_err(|e| format!("failed to store key {}: {}", key_id, e))?;
+    log::info!("Generated encryption key {}", key_id);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_key(key_id: String, app: AppHandle) -> Result<(), EchoError> {
+    match entry(&app, &key_id)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => {
+            log::info!("Deleted encryption key {}", key_id);
+            Ok(())
+        }
+        Err(e) => Err(format!("failed to delete key {}: {}", key_id, e).into()),
+    }
+}
+
+#[tauri::command]
+pub async fn encrypt_data(
+    plaintext: Vec<u8>,
+    key_id: String,
+    app: AppHandle,
+) -> Result<Vec<u8>, EchoError> {
+    let cipher = load_cipher(&app, &key_id)?;
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let sealed = cipher
+        .encrypt(&nonce, plaintext.as_slice())
+        .map_err(|_| "encryption failed")?;
+    let mut ciphertext = nonce.to_vec();
+    ciphertext.extend(sealed);
+    Ok(ciphertext)
+}
+
+#[tauri::command]
+pub async fn decrypt_data(
+    ciphertext: Vec<u8>,
+    key_id: String,
+    app: AppHandle,
+) -> Result<Vec<u8>, EchoError> {
+    if ciphertext.len() < NONCE_LEN {
+        return Err(EchoError::invalid("ciphertext is too short"));
+    }
+    let cipher = load_cipher(&app, &key_id)?;
+    let (nonce, sealed) = ciphertext.split_at(NONCE_LEN);
+    cipher
+        .decrypt(Nonce::from_slice(nonce), sealed)
+        .map_err(|_| EchoError::invalid("decryption failed: wrong key or corrupted data"))
+}
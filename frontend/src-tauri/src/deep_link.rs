@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use tauri::{App, AppHandle, Emitter, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+use crate::windows;
+
+/// An `echo://` link, e.g. `echo://open?id=123` becomes
+/// `{ action: "open", query: { id: "123" } }`.
+#[derive(Clone, Serialize)]
+pub struct DeepLink {
+    pub url: String,
+    pub action: String,
+    pub query: HashMap<String, String>,
+}
+
+impl From<&Url> for DeepLink {
+    fn from(url: &Url) -> Self {
+        Self {
+            url: url.to_string(),
+            action: url.host_str().unwrap_or_default().to_string(),
+            query: url.query_pairs().into_owned().collect(),
+        }
+    }
+}
+
+pub fn setup_deep_links(app: &App) {
+    // Installers register the scheme on Windows, but dev builds and AppImages don't
+    #[cfg(any(windows, target_os = "linux"))]
+    if let Err(e) = app.deep_link().register_all() {
+        log::warn!("Failed to register deep link schemes: {}", e);
+    }
+
+    let handle = app.handle().clone();
+    app.deep_link().on_open_url(move |event| {
+        for url in event.urls() {
+            forward(&handle, &url);
+        }
+    });
+}
+
+/// Bring the main window forward and hand the link to the frontend.
+pub fn forward(app: &AppHandle, url: &Url) {
+    log::info!("Opening deep link {}", url);
+    windows::focus_main(app);
+    let _ = app.emit("deep-link", DeepLink::from(url));
+}
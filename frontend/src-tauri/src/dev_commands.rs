@@ -0,0 +1,13 @@
+//! Commands only compiled into debug builds.
+
+use tauri::WebviewWindow;
+
+#[tauri::command]
+pub fn open_devtools(window: WebviewWindow) {
+    window.open_devtools();
+}
+
+#[tauri::command]
+pub fn close_devtools(window: WebviewWindow) {
+    window.close_devtools();
+}
@@ -0,0 +1,319 @@
+//! Self-checks for support workflows, and commands for end-to-end tests
+//! driving the app over IPC.
+
+use std::future::Future;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sysinfo::Disks;
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::app_config::{self, AppConfig};
+use crate::backend_proxy::BackendClient;
+use crate::error::EchoError;
+use crate::logs::now_ms;
+use crate::port::{self, PortState};
+use crate::progress::ProgressSubscriptions;
+use crate::recent_files::{self, RecentFile};
+use crate::registry::{self, SidecarInfo};
+use crate::sidecar::{self, BackendVersion, SidecarState, SidecarStatus};
+
+/// When `setup` finished.
+pub struct AppStartTime(pub Instant);
+
+/// Generated in `setup` and logged there, to tell apart the log lines of
+/// different runs.
+pub struct SessionId(pub Uuid);
+
+/// `pong:{ms since the epoch}`. Answered without touching any state, so it
+/// works whatever the backend is doing.
+#[tauri::command]
+pub fn ping() -> String {
+    format!("pong:{}", now_ms())
+}
+
+/// Managed state tests may want to assert on. Secrets such as the sidecar
+/// environment are left out or masked.
+#[derive(Serialize)]
+pub struct StateSnapshot {
+    pub sidecar: SidecarStatus,
+    pub backend_version: String,
+    pub sidecars: Vec<SidecarInfo>,
+    pub config: AppConfig,
+    pub progress_subscriptions: Vec<String>,
+    pub recent_files: Vec<RecentFile>,
+}
+
+#[tauri::command]
+pub async fn get_state_snapshot(app: AppHandle) -> StateSnapshot {
+    StateSnapshot {
+        sidecar: sidecar::get_sidecar_status(app.clone()).await,
+        backend_version: app.state::<Mutex<BackendVersion>>().lock().await.0.clone(),
+        sidecars: registry::list_sidecars(app.clone()).await,
+        config: app.state::<Mutex<AppConfig>>().lock().await.redacted(),
+        progress_subscriptions: app
+            .state::<Mutex<ProgressSubscriptions>>()
+            .lock()
+            .await
+            .operation_ids(),
+        recent_files: recent_files::get_recent_files(app.state()),
+    }
+}
+
+/// Each check is given up on after this long.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+const MIN_FREE_DISK_BYTES: u64 = 100 * 1024 * 1024;
+
+#[derive(Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+#[derive(Serialize)]
+pub struct DiagnosticCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct DiagnosticReport {
+    pub checks: Vec<DiagnosticCheck>,
+    pub app_uptime_secs: u64,
+    pub sidecar_uptime_secs: Option<u64>,
+}
+
+type CheckResult = (CheckStatus, Option<String>);
+
+async fn timed(name: &str, check: impl Future<Output = CheckResult>) -> DiagnosticCheck {
+    let (status, detail) = tokio::time::timeout(CHECK_TIMEOUT, check)
+        .await
+        .unwrap_or_else(|_| (CheckStatus::Fail, Some("timed out".into())));
+    DiagnosticCheck {
+        name: name.into(),
+        status,
+        detail,
+    }
+}
+
+async fn backend_reachable(app: &AppHandle) -> CheckResult {
+    let port = app.state::<Mutex<PortState>>().lock().await.0;
+    let backend = match app.state::<BackendClient>().current(port) {
+        Ok(backend) => backend,
+        Err(e) => return (CheckStatus::Fail, Some(e.to_string())),
+    };
+    match backend.health().await {
+        Ok(()) => (CheckStatus::Pass, None),
+        Err(e) => (CheckStatus::Fail, Some(e.to_string())),
+    }
+}
+
+async fn backend_version(app: &AppHandle) -> CheckResult {
+    let found = app.state::<Mutex<BackendVersion>>().lock().await.0.clone();
+    let required = sidecar::min_backend_version();
+    match semver::Version::parse(&found) {
+        Ok(version) if version >= required => (CheckStatus::Pass, Some(found)),
+        Ok(_) => (
+            CheckStatus::Fail,
+            Some(format!("{} is older than required {}", found, required)),
+        ),
+        Err(_) => (
+            CheckStatus::Warn,
+            Some(format!("backend version unknown ({})", found)),
+        ),
+    }
+}
+
+/// Free space on the disk holding the app data directory.
+fn disk_space(app: &AppHandle) -> CheckResult {
+    let dir = match app.path().app_data_dir() {
+        Ok(dir) => dir,
+        Err(e) => return (CheckStatus::Fail, Some(e.to_string())),
+    };
+    let disks = Disks::new_with_refreshed_list();
+    let disk = disks
+        .list()
+        .iter()
+        .filter(|disk| dir.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len());
+    let Some(disk) = disk else {
+        return (
+            CheckStatus::Warn,
+            Some("could not find the data disk".into()),
+        );
+    };
+    let free = disk.available_space();
+    let detail = Some(format!("{} MB free", free / 1024 / 1024));
+    if free > MIN_FREE_DISK_BYTES {
+        (CheckStatus::Pass, detail)
+    } else {
+        (CheckStatus::Fail, detail)
+    }
+}
+
+/// Whether a port in the backend's range can be listened on.
+fn port_available() -> CheckResult {
+    match port::first_free_port() {
+        Ok(port) => (CheckStatus::Pass, Some(format!("port {} is free", port))),
+        Err(e) => (CheckStatus::Fail, Some(e.to_string())),
+    }
+}
+
+fn log_dir_writable(app: &AppHandle) -> CheckResult {
+    let write_probe = |dir: &Path| -> std::io::Result<()> {
+        std::fs::create_dir_all(dir)?;
+        let probe = dir.join(".write-test");
+        std::fs::write(&probe, b"")?;
+        std::fs::remove_file(probe)
+    };
+    match app.path().app_log_dir() {
+        Ok(dir) => match write_probe(&dir) {
+            Ok(()) => (CheckStatus::Pass, None),
+            Err(e) => (CheckStatus::Fail, Some(format!("{}: {}", dir.display(), e))),
+        },
+        Err(e) => (CheckStatus::Fail, Some(e.to_string())),
+    }
+}
+
+/// GET the first updater endpoint from `tauri.conf.json`.
+async fn update_channel(app: &AppHandle) -> CheckResult {
+    let endpoint = app
+        .config()
+        .plugins
+        .0
+        .get("updater")
+        .and_then(|updater| updater.get("endpoints"))
+        .and_then(|endpoints| endpoints.get(0))
+        .and_then(|endpoint| endpoint.as_str())
+        .map(String::from);
+    let Some(endpoint) = endpoint else {
+        return (
+            CheckStatus::Warn,
+            Some("no update endpoint configured".into()),
+        );
+    };
+    match reqwest::get(&endpoint).await {
+        Ok(resp) if resp.status().is_success() => (CheckStatus::Pass, None),
+        Ok(resp) => (
+            CheckStatus::Warn,
+            Some(format!("{} answered {}", endpoint, resp.status())),
+        ),
+        Err(e) => (CheckStatus::Fail, Some(e.without_url().to_string())),
+    }
+}
+
+fn config_file(app: &AppHandle) -> CheckResult {
+    let path = match app_config::settings_path(app) {
+        Ok(path) => path,
+        Err(e) => return (CheckStatus::Fail, Some(e.to_string())),
+    };
+    match std::fs::read_to_string(&path) {
+        Ok(text) => match toml::from_str::<AppConfig>(&text) {
+            Ok(_) => (CheckStatus::Pass, None),
+            Err(e) => (CheckStatus::Fail, Some(e.to_string())),
+        },
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => (
+            CheckStatus::Warn,
+            Some(format!("{} is missing, using defaults", path.display())),
+        ),
+        Err(e) => (
+            CheckStatus::Fail,
+            Some(format!("{}: {}", path.display(), e)),
+        ),
+    }
+}
+
+/// Check every subsystem at once, for a support "run diagnostics" button.
+#[tauri::command]
+pub async fn run_diagnostic(app: AppHandle) -> DiagnosticReport {
+    let (reachable, version, disk, port, log_dir, updates, config) = tokio::join!(
+        timed("backend reachable", backend_reachable(&app)),
+        timed("backend version compatible", backend_version(&app)),
+        timed("disk space", async { disk_space(&app) }),
+        timed("port available", async { port_available() }),
+        timed("log directory writable", async { log_dir_writable(&app) }),
+        timed("update channel reachable", update_channel(&app)),
+        timed("config file parseable", async { config_file(&app) }),
+    );
+    DiagnosticReport {
+        checks: vec![reachable, version, disk, port, log_dir, updates, config],
+        app_uptime_secs: get_app_uptime(app.state()),
+        sidecar_uptime_secs: get_sidecar_uptime(app.clone()).await,
+    }
+}
+
+/// The bundled backend binary, for telling which build a user is running.
+#[derive(Serialize)]
+pub struct BinaryInfo {
+    pub path: String,
+    pub sha256: String,
+    pub size: u64,
+    /// Contents of `.build-info.json` next to the binary (build profile, Git
+    /// commit and so on), or null if the build didn't write one.
+    pub build_info: serde_json::Value,
+}
+
+/// Sidecars are bundled next to the app executable rather than with the
+/// other resources, so look for the backend there.
+#[tauri::command]
+pub async fn get_sidecar_binary_info() -> Result<BinaryInfo, EchoError> {
+    let dir = std::env::current_exe()?
+        .parent()
+        .ok_or("app executable has no parent directory")?
+        .to_path_buf();
+    let path = dir
+        .join(sidecar::sidecar_name())
+        .with_extension(std::env::consts::EXE_EXTENSION);
+
+    let hash_path = path.clone();
+    let (sha256, size) = tauri::async_runtime::spawn_blocking(move || {
+        let mut file = std::fs::File::open(&hash_path)?;
+        let mut hasher = Sha256::new();
+        let size = std::io::copy(&mut file, &mut hasher)?;
+        Ok::<_, std::io::Error>((format!("{:x}", hasher.finalize()), size))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+
+    let build_info = match tokio::fs::read_to_string(dir.join(".build-info.json")).await {
+        Ok(text) => serde_json::from_str(&text)?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => serde_json::Value::Null,
+        Err(e) => return Err(e.into()),
+    };
+    Ok(BinaryInfo {
+        path: path.display().to_string(),
+        sha256,
+        size,
+        build_info,
+    })
+}
+
+/// Seconds since `setup` finished.
+#[tauri::command]
+pub fn get_app_uptime(start: State<'_, AppStartTime>) -> u64 {
+    start.0.elapsed().as_secs()
+}
+
+/// Seconds since the backend was last (re)started, `None` while it isn't running.
+#[tauri::command]
+pub async fn get_sidecar_uptime(handle: AppHandle) -> Option<u64> {
+    let state = handle.state::<Mutex<SidecarState>>();
+    let guard = state.lock().await;
+    guard
+        .start_time
+        .filter(|_| guard.child.is_some())
+        .map(|start| start.elapsed().as_secs())
+}
+
+#[tauri::command]
+pub fn get_session_id(session: State<'_, SessionId>) -> String {
+    session.0.to_string()
+}
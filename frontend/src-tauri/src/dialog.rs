@@ -0,0 +1,178 @@
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_dialog::{DialogExt, FileDialogBuilder, FilePath};
+use tauri_plugin_fs::FsExt;
+use tokio::sync::oneshot;
+
+use crate::error::EchoError;
+use crate::file_drop;
+
+#[derive(Deserialize)]
+pub struct FileFilter {
+    pub name: String,
+    pub extensions: Vec<String>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct FileDialogOptions {
+    pub filters: Vec<FileFilter>,
+    pub default_dir: Option<String>,
+}
+
+fn builder(app: &AppHandle, options: &FileDialogOptions) -> FileDialogBuilder<tauri::Wry> {
+    let mut builder = app.dialog().file();
+    for filter in &options.filters {
+        let extensions: Vec<&str> = filter.extensions.iter().map(String::as_str).collect();
+        builder = builder.add_filter(&filter.name, &extensions);
+    }
+    if let Some(dir) = &options.default_dir {
+        builder = builder.set_directory(dir);
+    }
+    builder
+}
+
+/// Run a callback-style dialog without blocking the async runtime.
+async fn show<T: Send + 'static>(open: impl FnOnce(oneshot::Sender<T>)) -> Result<T, String> {
+    let (tx, rx) = oneshot::channel();
+    open(tx);
+    rx.await
+        .map_err(|_| "dialog closed unexpectedly".to_string())
+}
+
+/// On macOS and Linux the chosen path must be inside the home directory. It
+/// is then added to the fs scope, as the dialog plugin's own commands do, so
+/// commands that only accept picked paths let it through.
+fn check(app: &AppHandle, path: FilePath, directory: bool) -> Result<PathBuf, EchoError> {
+    let mut path = path.into_path().map_err(|e| e.to_string())?;
+    if cfg!(unix) {
+        path = file_drop::resolve_within(&file_drop::home_dir(app)?, &path)
+            .map_err(EchoError::invalid)?;
+    }
+    allow(app, &path, directory)?;
+    Ok(path)
+}
+
+fn allow<R: Runtime>(app: &AppHandle<R>, path: &Path, directory: bool) -> Result<(), EchoError> {
+    let scope = app.fs_scope();
+    let allowed = if directory {
+        scope.allow_directory(path, true)
+    } else {
+        scope.allow_file(path)
+    };
+    allowed.map_err(|e| format!("failed to allow {}: {}", path.display(), e).into())
+}
+
+/// Whether `path` is, or is inside, one the user chose in a dialog this run.
+pub fn is_picked<R: Runtime>(app: &AppHandle<R>, path: &Path) -> bool {
+    app.fs_scope().is_allowed(path)
+}
+
+fn to_string(path: PathBuf) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+#[tauri::command]
+pub async fn pick_file(
+    app: AppHandle,
+    options: FileDialogOptions,
+) -> Result<Option<String>, EchoError> {
+    let builder = builder(&app, &options);
+    let picked = show(|tx| {
+        builder.pick_file(move |path| {
+            let _ = tx.send(path);
+        })
+    })
+    .await?;
+    picked
+        .map(|path| check(&app, path, false).map(to_string))
+        .transpose()
+}
+
+#[tauri::command]
+pub async fn pick_directory(
+    app: AppHandle,
+    options: FileDialogOptions,
+) -> Result<Option<String>, EchoError> {
+    let builder = builder(&app, &options);
+    let picked = show(|tx| {
+        builder.pick_folder(move |path| {
+            let _ = tx.send(path);
+        })
+    })
+    .await?;
+    picked
+        .map(|path| check(&app, path, true).map(to_string))
+        .transpose()
+}
+
+#[tauri::command]
+pub async fn save_file(
+    app: AppHandle,
+    options: FileDialogOptions,
+) -> Result<Option<String>, EchoError> {
+    let builder = builder(&app, &options);
+    let picked = show(|tx| {
+        builder.save_file(move |path| {
+            let _ = tx.send(path);
+        })
+    })
+    .await?;
+    picked
+        .map(|path| check(&app, path, false).map(to_string))
+        .transpose()
+}
+
+/// Click-to-import alternative to dropping files on the window. Results are
+/// reported through the same `files-imported` / `import-error` events.
+#[tauri::command]
+pub async fn pick_files_to_import(
+    app: AppHandle,
+    options: FileDialogOptions,
+) -> Result<(), EchoError> {
+    let builder = builder(&app, &options);
+    let picked = show(|tx| {
+        builder.pick_files(move |paths| {
+            let _ = tx.send(paths);
+        })
+    })
+    .await?;
+    let paths = picked
+        .unwrap_or_default()
+        .into_iter()
+        .map(FilePath::into_path)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    if !paths.is_empty() {
+        file_drop::handle_drop(&app, paths);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_picked_paths_are_allowed() {
+        let app = tauri::test::mock_builder()
+            .plugin(tauri_plugin_fs::init())
+            .build(tauri::test::mock_context(tauri::test::noop_assets()))
+            .unwrap();
+        let app = app.handle();
+        let dir = std::env::temp_dir().join(format!("echo-dialog-{}", uuid::Uuid::new_v4()));
+        let file = dir.join("logs.ndjson");
+        let folder = dir.join("exports");
+
+        allow(app, &file, false).unwrap();
+        allow(app, &folder, true).unwrap();
+
+        assert!(is_picked(app, &file));
+        assert!(is_picked(app, &folder.join("2024/settings.json")));
+        assert!(!is_picked(app, &dir.join("other.ndjson")));
+        assert!(!is_picked(app, &dir));
+        assert!(!is_picked(app, Path::new("/etc/passwd")));
+    }
+}
@@ -0,0 +1,229 @@
+//! Large downloads such as model weights, written to `{dest}.part` and moved
+//! to `dest` when complete. A `.part` file left by a cancelled or failed
+//! download is resumed with a `Range` request.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+
+use crate::dialog;
+use crate::error::EchoError;
+use crate::file_drop::resolve_within;
+
+/// How often `download-progress` is emitted at most.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Cancellation tokens of the running downloads, by URL.
+#[derive(Default)]
+pub struct Downloads(HashMap<String, CancellationToken>);
+
+#[derive(Clone, Serialize)]
+struct DownloadProgress<'a> {
+    url: &'a str,
+    bytes_received: u64,
+    /// Unknown if the server doesn't send a length.
+    total_bytes: Option<u64>,
+}
+
+fn part_path(dest: &Path) -> PathBuf {
+    let mut name = dest.as_os_str().to_owned();
+    name.push(".part");
+    PathBuf::from(name)
+}
+
+/// `path` resolved, if it is inside the downloads or app data directory or was
+/// chosen in a dialog.
+pub fn allowed_path<R: Runtime>(app: &AppHandle<R>, path: &Path) -> Option<PathBuf> {
+    if dialog::is_picked(app, path) {
+        return Some(path.to_path_buf());
+    }
+    [app.path().download_dir(), app.path().app_data_dir()]
+        .into_iter()
+        .filter_map(|dir| dir.ok()?.canonicalize().ok())
+        .find_map(|dir| resolve_within(&dir, path).ok())
+}
+
+/// Download `url` to `dest`, which must be inside the downloads or app data
+/// directory or chosen with `save_file`, emitting `download-progress` events
+/// as it goes. Starting a download of a URL that is already downloading
+/// cancels the first one.
+#[tauri::command]
+pub async fn download_file(url: String, dest: String, app: AppHandle) -> Result<(), EchoError> {
+    let Some(dest) = allowed_path(&app, Path::new(&dest)) else {
+        log::warn!("Refusing to download to {}", dest);
+        return Err(EchoError::Unauthorized);
+    };
+
+    let token = CancellationToken::new();
+    let downloads = app.state::<Mutex<Downloads>>();
+    if let Some(old) = downloads.lock().await.0.insert(url.clone(), token.clone()) {
+        old.cancel();
+    }
+
+    let result = tokio::select! {
+        _ = token.cancelled() => Err("download cancelled".into()),
+        result = download(&app, &url, &dest) => result,
+    };
+    if !token.is_cancelled() {
+        downloads.lock().await.0.remove(&url);
+    }
+    result
+}
+
+async fn download(app: &AppHandle, url: &str, dest: &Path) -> Result<(), EchoError> {
+    let part = part_path(dest);
+    let offset = match tokio::fs::metadata(&part).await {
+        Ok(metadata) => metadata.len(),
+        Err(_) => 0,
+    };
+
+    let mut request = reqwest::Client::new().get(url);
+    if offset > 0 {
+        request = request.header(reqwest::header::RANGE, format!("bytes={}-", offset));
+    }
+    let resp = request
+        .send()
+        .await
+        .map_err(|e| format!("download failed: {}", e))?;
+    if offset > 0 && resp.status() == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+        return finish_stale_part(app, url, dest, &part, offset, &resp).await;
+    }
+    let mut resp = resp
+        .error_for_status()
+        .map_err(|e| format!("download failed: {}", e))?;
+
+    // Servers that ignore `Range` send the whole file again
+    let resumed = resp.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+    let mut received = if resumed { offset } else { 0 };
+    let total = resp.content_length().map(|length| received + length);
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(&part)
+        .await?;
+    if resumed {
+        log::info!("Resuming download of {} at {} bytes", url, offset);
+    }
+
+    let mut last_emit = Instant::now();
+    while let Some(chunk) = resp
+        .chunk()
+        .await
+        .map_err(|e| format!("download failed: {}", e))?
+    {
+        file.write_all(&chunk).await?;
+        received += chunk.len() as u64;
+        if last_emit.elapsed() >= PROGRESS_INTERVAL {
+            emit_progress(app, url, received, total);
+            last_emit = Instant::now();
+        }
+    }
+    file.flush().await?;
+    drop(file);
+    emit_progress(app, url, received, total);
+
+    tokio::fs::rename(&part, dest).await?;
+    log::info!("Downloaded {} to {}", url, dest.display());
+    Ok(())
+}
+
+/// The server has nothing after `offset`: the `.part` file is either the
+/// whole file, when a previous download was stopped just before the rename,
+/// or longer than the file now is, in which case it starts over.
+async fn finish_stale_part(
+    app: &AppHandle,
+    url: &str,
+    dest: &Path,
+    part: &Path,
+    offset: u64,
+    resp: &reqwest::Response,
+) -> Result<(), EchoError> {
+    // `Content-Range: bytes */<length>`
+    let length = resp
+        .headers()
+        .get(reqwest::header::CONTENT_RANGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("bytes */"))
+        .and_then(|length| length.parse::<u64>().ok());
+    if length.map_or(true, |length| length == offset) {
+        emit_progress(app, url, offset, Some(offset));
+        tokio::fs::rename(part, dest).await?;
+        log::info!("Downloaded {} to {}", url, dest.display());
+        return Ok(());
+    }
+
+    log::warn!(
+        "Partial download of {} is larger than the file, starting over",
+        url
+    );
+    tokio::fs::remove_file(part).await?;
+    Box::pin(download(app, url, dest)).await
+}
+
+fn emit_progress(app: &AppHandle, url: &str, bytes_received: u64, total_bytes: Option<u64>) {
+    let _ = app.emit(
+        "download-progress",
+        DownloadProgress {
+            url,
+            bytes_received,
+            total_bytes,
+        },
+    );
+}
+
+/// Stop downloading `url`, keeping what was received so far for a later
+/// `download_file` to resume. Returns whether it was downloading.
+#[tauri::command]
+pub async fn cancel_download(url: String, app: AppHandle) -> bool {
+    let token = app.state::<Mutex<Downloads>>().lock().await.0.remove(&url);
+    match token {
+        Some(token) => {
+            token.cancel();
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part_file_sits_next_to_the_destination() {
+        assert_eq!(
+            part_path(Path::new("/downloads/exam.pdf")),
+            Path::new("/downloads/exam.pdf.part")
+        );
+        assert_eq!(
+            part_path(Path::new("downloads/archive")),
+            Path::new("downloads/archive.part")
+        );
+    }
+
+    #[test]
+    fn only_picked_or_app_paths_are_allowed() {
+        use tauri_plugin_fs::FsExt;
+
+        let app = tauri::test::mock_builder()
+            .plugin(tauri_plugin_fs::init())
+            .build(tauri::test::mock_context(tauri::test::noop_assets()))
+            .unwrap();
+        let app = app.handle();
+        let dir = std::env::temp_dir().join(format!("echo-download-{}", uuid::Uuid::new_v4()));
+        let picked = dir.join("model.bin");
+        app.fs_scope().allow_file(&picked).unwrap();
+
+        assert_eq!(allowed_path(app, &picked), Some(picked));
+        assert_eq!(allowed_path(app, &dir.join("other.bin")), None);
+        assert_eq!(allowed_path(app, Path::new("/etc/cron.d/job")), None);
+    }
+}
@@ -0,0 +1,150 @@
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::port::PortError;
+
+/// Error returned by every command. Serialized as an object tagged with
+/// `code` so the frontend can tell failures apart without parsing messages.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "code")]
+pub enum EchoError {
+    SidecarNotRunning,
+    /// Nothing reached the backend, so the request is safe to send again.
+    BackendUnreachable {
+        url: String,
+        source: String,
+    },
+    /// The backend was reached but the request failed: an error status, a
+    /// timeout or a response that couldn't be read.
+    BackendFailed {
+        url: String,
+        source: String,
+    },
+    PortExhausted {
+        start: u16,
+        end: u16,
+    },
+    Io {
+        message: String,
+    },
+    Serialization {
+        message: String,
+    },
+    Unauthorized,
+    RateLimited,
+    /// The arguments were rejected before anything was attempted.
+    InvalidInput {
+        message: String,
+    },
+    /// Anything not covered above, e.g. a failing plugin or OS API.
+    Other {
+        message: String,
+    },
+}
+
+impl EchoError {
+    /// A request to the backend that failed to get a successful response.
+    pub fn backend(e: reqwest::Error) -> Self {
+        let url = e.url().map(|url| url.to_string()).unwrap_or_default();
+        let source = e.to_string();
+        if e.is_connect() {
+            EchoError::BackendUnreachable { url, source }
+        } else {
+            EchoError::BackendFailed { url, source }
+        }
+    }
+
+    pub fn invalid(message: impl Into<String>) -> Self {
+        EchoError::InvalidInput {
+            message: message.into(),
+        }
+    }
+
+    pub fn io(message: impl Into<String>) -> Self {
+        EchoError::Io {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for EchoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EchoError::SidecarNotRunning => write!(f, "backend not running"),
+            EchoError::BackendUnreachable { url, source }
+            | EchoError::BackendFailed { url, source } => {
+                write!(f, "backend request to {} failed: {}", url, source)
+            }
+            EchoError::PortExhausted { start, end } => {
+                write!(
+                    f,
+                    "{}",
+                    PortError::Exhausted {
+                        start: *start,
+                        end: *end
+                    }
+                )
+            }
+            EchoError::Unauthorized => write!(f, "not allowed"),
+            EchoError::RateLimited => write!(f, "too many requests, try again later"),
+            EchoError::Io { message }
+            | EchoError::Serialization { message }
+            | EchoError::InvalidInput { message }
+            | EchoError::Other { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for EchoError {}
+
+impl From<String> for EchoError {
+    fn from(message: String) -> Self {
+        EchoError::Other { message }
+    }
+}
+
+impl From<&str> for EchoError {
+    fn from(message: &str) -> Self {
+        message.to_string().into()
+    }
+}
+
+impl From<std::io::Error> for EchoError {
+    fn from(e: std::io::Error) -> Self {
+        EchoError::io(e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for EchoError {
+    fn from(e: serde_json::Error) -> Self {
+        EchoError::Serialization {
+            message: e.to_string(),
+        }
+    }
+}
+
+impl From<toml::de::Error> for EchoError {
+    fn from(e: toml::de::Error) -> Self {
+        EchoError::Serialization {
+            message: e.to_string(),
+        }
+    }
+}
+
+impl From<toml::ser::Error> for EchoError {
+    fn from(e: toml::ser::Error) -> Self {
+        EchoError::Serialization {
+            message: e.to_string(),
+        }
+    }
+}
+
+impl From<PortError> for EchoError {
+    fn from(e: PortError) -> Self {
+        match e {
+            PortError::Exhausted { start, end } => EchoError::PortExhausted { start, end },
+            PortError::NoAttempts { .. } => EchoError::invalid(e.to_string()),
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+
+use crate::error::EchoError;
+use crate::logs::LogEntry;
+use crate::sidecar::AppVersion;
+use crate::system::{self, SystemInfo};
+
+/// Where reports go, baked in at build time. `FEEDBACK_URL_ENV` overrides it.
+const FEEDBACK_URL: Option<&str> = option_env!("ECHO_FEEDBACK_URL");
+const FEEDBACK_URL_ENV: &str = "ECHO_FEEDBACK_URL";
+/// Sent as a bearer token when set.
+const FEEDBACK_API_KEY_ENV: &str = "ECHO_FEEDBACK_API_KEY";
+
+const MAX_REPORTS: usize = 3;
+const RATE_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Serialize, Deserialize)]
+pub struct BugReport {
+    pub description: String,
+    pub logs: Vec<LogEntry>,
+    pub screenshot_b64: Option<String>,
+    pub version: AppVersion,
+    /// Filled in by `send_feedback`.
+    #[serde(default)]
+    pub system: Option<SystemInfo>,
+}
+
+/// When recent reports were sent, oldest first.
+#[derive(Default)]
+pub struct ReportRateLimit(Mutex<VecDeque<Instant>>);
+
+impl ReportRateLimit {
+    /// Record a report unless `MAX_REPORTS` were already sent within `RATE_WINDOW`.
+    fn try_acquire(&self) -> bool {
+        let mut sent = self.0.lock().unwrap();
+        while sent.front().is_some_and(|at| at.elapsed() >= RATE_WINDOW) {
+            sent.pop_front();
+        }
+        if sent.len() >= MAX_REPORTS {
+            return false;
+        }
+        sent.push_back(Instant::now());
+        true
+    }
+}
+
+/// Keep just enough of a secret to tell keys apart in logs.
+fn mask(key: &str) -> String {
+    let prefix: String = key.chars().take(4).collect();
+    format!("{}****", prefix)
+}
+
+#[tauri::command]
+pub async fn send_feedback(
+    mut report: BugReport,
+    app: AppHandle,
+    limit: State<'_, ReportRateLimit>,
+) -> Result<(), EchoError> {
+    let url = std::env::var(FEEDBACK_URL_ENV)
+        .ok()
+        .or(FEEDBACK_URL.map(String::from))
+        .ok_or("no feedback endpoint configured")?;
+    if !limit.try_acquire() {
+        return Err(EchoError::RateLimited);
+    }
+
+    report.system = Some(system::system_info(&app));
+    let mut request = reqwest::Client::new()
+        .post(&url)
+        .timeout(Duration::from_secs(30))
+        .json(&report);
+    match std::env::var(FEEDBACK_API_KEY_ENV) {
+        Ok(key) => {
+            log::info!("Sending feedback to {} with key {}", url, mask(&key));
+            request = request.bearer_auth(key);
+        }
+        Err(_) => log::info!("Sending feedback to {}", url),
+    }
+
+    request
+        .send()
+        .await
+        .and_then(|resp| resp.error_for_status())
+        .map_err(|e| match e.status() {
+            Some(reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN) => {
+                EchoError::Unauthorized
+            }
+            Some(reqwest::StatusCode::TOO_MANY_REQUESTS) => EchoError::RateLimited,
+            _ => format!("failed to send feedback: {}", e.without_url()).into(),
+        })?;
+    Ok(())
+}
@@ -0,0 +1,163 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::Mutex;
+
+use crate::backend_proxy::BackendClient;
+use crate::port::PortState;
+use crate::sidecar::SidecarState;
+
+#[derive(Clone, Serialize)]
+struct FilesImported {
+    count: usize,
+}
+
+#[derive(Clone, Serialize)]
+struct ImportError {
+    message: String,
+}
+
+/// Hand files dropped onto a window to the backend's `/import` endpoint.
+pub fn handle_drop(app: &AppHandle, paths: Vec<PathBuf>) {
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = match validate(&handle, &paths) {
+            Ok(paths) => import(&handle, &paths).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(Some(count)) => {
+                let _ = handle.emit("files-imported", FilesImported { count });
+            }
+            Ok(None) => log::warn!("Ignoring dropped files, backend is not running"),
+            Err(message) => {
+                log::warn!("File import failed: {}", message);
+                let _ = handle.emit("import-error", ImportError { message });
+            }
+        }
+    });
+}
+
+/// Resolve every path and make sure it lives under the user's home directory.
+fn validate(app: &AppHandle, paths: &[PathBuf]) -> Result<Vec<PathBuf>, String> {
+    let home = home_dir(app)?;
+    paths
+        .iter()
+        .map(|path| resolve_within(&home, path))
+        .collect()
+}
+
+pub fn home_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .home_dir()
+        .ok()
+        .and_then(|home| home.canonicalize().ok())
+        .ok_or_else(|| "could not resolve the home directory".into())
+}
+
+/// Resolve `..` and symlinks in `path` and check the result is under `root`.
+/// `path` itself doesn't have to exist yet, but its parent does.
+pub fn resolve_within(root: &Path, path: &Path) -> Result<PathBuf, String> {
+    let resolved = match path.canonicalize() {
+        Ok(resolved) => Some(resolved),
+        Err(_) => path
+            .parent()
+            .zip(path.file_name())
+            .and_then(|(parent, name)| Some(parent.canonicalize().ok()?.join(name))),
+    };
+    resolved
+        .filter(|resolved| resolved.starts_with(root))
+        .ok_or_else(|| format!("{} is outside the home directory", path.display()))
+}
+
+/// Returns `Ok(None)` without sending anything if neither the sidecar nor a
+/// remote backend is available.
+async fn import(app: &AppHandle, paths: &[PathBuf]) -> Result<Option<usize>, String> {
+    let port = app.state::<Mutex<PortState>>().lock().await.0;
+    let running = app
+        .state::<Mutex<SidecarState>>()
+        .lock()
+        .await
+        .child
+        .is_some();
+    let Ok(backend) = app
+        .state::<BackendClient>()
+        .current(port.filter(|_| running))
+    else {
+        return Ok(None);
+    };
+
+    let count = backend
+        .import(paths)
+        .await
+        .map_err(|e| format!("backend rejected import: {}", e))?;
+    Ok(Some(count))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh canonical directory with `root/` inside it, removed on drop.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> Self {
+            let dir = std::env::temp_dir().join(format!("echo-resolve-{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(dir.join("root/sub")).unwrap();
+            Self(dir.canonicalize().unwrap())
+        }
+
+        fn root(&self) -> PathBuf {
+            self.0.join("root")
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn existing_and_new_files_inside_are_allowed() {
+        let dir = TempDir::new();
+        let root = dir.root();
+        std::fs::write(root.join("sub/exam.pdf"), b"").unwrap();
+
+        assert_eq!(
+            resolve_within(&root, &root.join("sub/exam.pdf")),
+            Ok(root.join("sub/exam.pdf"))
+        );
+        assert_eq!(
+            resolve_within(&root, &root.join("sub/new.pdf")),
+            Ok(root.join("sub/new.pdf"))
+        );
+        assert_eq!(
+            resolve_within(&root, &root.join("sub/../sub/exam.pdf")),
+            Ok(root.join("sub/exam.pdf"))
+        );
+    }
+
+    #[test]
+    fn escaping_or_unresolvable_paths_are_rejected() {
+        let dir = TempDir::new();
+        let root = dir.root();
+        std::fs::write(dir.0.join("secret"), b"").unwrap();
+
+        assert!(resolve_within(&root, &root.join("../secret")).is_err());
+        assert!(resolve_within(&root, &root.join("missing/file")).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_out_of_the_root_are_rejected() {
+        let dir = TempDir::new();
+        let root = dir.root();
+        std::fs::write(dir.0.join("secret"), b"").unwrap();
+        std::os::unix::fs::symlink(dir.0.join("secret"), root.join("link")).unwrap();
+
+        assert!(resolve_within(&root, &root.join("link")).is_err());
+    }
+}
@@ -0,0 +1,175 @@
+//! File checksums, e.g. to verify a file after `download_file`.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+use tauri::{AppHandle, Emitter};
+use tokio::io::AsyncReadExt;
+
+use crate::downloads;
+use crate::error::EchoError;
+
+const CHUNK_SIZE: usize = 64 * 1024;
+/// `hash-progress` is emitted each time this many more bytes were hashed.
+const PROGRESS_STEP: u64 = 1024 * 1024;
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgo {
+    Sha256,
+    Sha512,
+    Blake3,
+}
+
+enum Hasher {
+    Sha256(sha2::Sha256),
+    Sha512(sha2::Sha512),
+    Blake3(Box<blake3::Hasher>),
+}
+
+impl Hasher {
+    fn new(algorithm: HashAlgo) -> Self {
+        match algorithm {
+            HashAlgo::Sha256 => Hasher::Sha256(sha2::Sha256::new()),
+            HashAlgo::Sha512 => Hasher::Sha512(sha2::Sha512::new()),
+            HashAlgo::Blake3 => Hasher::Blake3(Box::default()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Sha256(hasher) => hasher.update(data),
+            Hasher::Sha512(hasher) => hasher.update(data),
+            Hasher::Blake3(hasher) => {
+                hasher.update(data);
+            }
+        }
+    }
+
+    fn finalize_hex(self) -> String {
+        match self {
+            Hasher::Sha256(hasher) => format!("{:x}", hasher.finalize()),
+            Hasher::Sha512(hasher) => format!("{:x}", hasher.finalize()),
+            Hasher::Blake3(hasher) => hasher.finalize().to_hex().to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+struct HashProgress<'a> {
+    path: &'a str,
+    bytes_processed: u64,
+    total_bytes: u64,
+}
+
+/// Hex digest of the file at `path`, which must be inside the downloads or app
+/// data directory or chosen in a dialog. Emits `hash-progress` for every
+/// megabyte hashed.
+#[tauri::command]
+pub async fn compute_file_hash(
+    path: String,
+    algorithm: HashAlgo,
+    app: AppHandle,
+) -> Result<String, EchoError> {
+    let Some(resolved) = downloads::allowed_path(&app, Path::new(&path)) else {
+        log::warn!("Refusing to hash {}", path);
+        return Err(EchoError::Unauthorized);
+    };
+
+    Ok(
+        hash_file(&resolved, algorithm, |bytes_processed, total_bytes| {
+            let _ = app.emit(
+                "hash-progress",
+                HashProgress {
+                    path: &path,
+                    bytes_processed,
+                    total_bytes,
+                },
+            );
+        })
+        .await?,
+    )
+}
+
+/// Calls `on_progress` with the bytes hashed so far and the file size.
+async fn hash_file(
+    path: &Path,
+    algorithm: HashAlgo,
+    mut on_progress: impl FnMut(u64, u64),
+) -> std::io::Result<String> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let total_bytes = file.metadata().await?.len();
+    let mut hasher = Hasher::new(algorithm);
+    let mut buf = vec![0; CHUNK_SIZE];
+    let mut bytes_processed = 0;
+    let mut next_progress = PROGRESS_STEP;
+    loop {
+        let read = file.read(&mut buf).await?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buf[..read]);
+        bytes_processed += read as u64;
+        if bytes_processed >= next_progress {
+            on_progress(bytes_processed, total_bytes);
+            next_progress += PROGRESS_STEP;
+        }
+    }
+    Ok(hasher.finalize_hex())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn hash_of(contents: &[u8], algorithm: HashAlgo) -> String {
+        let path = std::env::temp_dir().join(format!("echo-hash-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, contents).unwrap();
+        let hash = hash_file(&path, algorithm, |_, _| {}).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+        hash
+    }
+
+    #[tokio::test]
+    async fn matches_known_vectors() {
+        assert_eq!(
+            hash_of(b"abc", HashAlgo::Sha256).await,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            hash_of(b"abc", HashAlgo::Sha512).await,
+            "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+             2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+        );
+        assert_eq!(
+            hash_of(b"abc", HashAlgo::Blake3).await,
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+        );
+        assert_eq!(
+            hash_of(b"", HashAlgo::Sha256).await,
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            hash_of(b"", HashAlgo::Blake3).await,
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_progress_every_step() {
+        let path = std::env::temp_dir().join(format!("echo-hash-{}", uuid::Uuid::new_v4()));
+        let len = 2 * PROGRESS_STEP + PROGRESS_STEP / 2;
+        std::fs::write(&path, vec![0u8; len as usize]).unwrap();
+
+        let mut progress = Vec::new();
+        hash_file(&path, HashAlgo::Sha256, |done, total| {
+            progress.push((done, total))
+        })
+        .await
+        .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(progress, [(PROGRESS_STEP, len), (2 * PROGRESS_STEP, len)]);
+    }
+}
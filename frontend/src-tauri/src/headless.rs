@@ -0,0 +1,112 @@
+//! `--headless`: run the backend on its own, without a window, for servers.
+//!
+//! The backend's output goes straight to this process's stdout and stderr.
+//! TLS and the other settings from `tauri.conf.json` need the GUI app, so
+//! the backend runs with the built-in defaults.
+
+use std::error::Error;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use tokio::process::{Child, Command};
+
+use crate::backend_proxy::{BackendClient, ProxyConfig};
+use crate::port;
+use crate::sidecar::{self, ShutdownConfig, SidecarConfig};
+use crate::signals::shutdown_signal;
+use crate::watchdog::WatchdogConfig;
+
+pub fn is_requested() -> bool {
+    std::env::args().skip(1).any(|arg| arg == "--headless")
+}
+
+/// Start the backend on the first free port and keep it running until interrupted.
+pub fn run() -> Result<(), Box<dyn Error>> {
+    log::set_logger(&StderrLogger).map(|()| log::set_max_level(log::LevelFilter::Info))?;
+    let port = port::first_free_port()?;
+    tauri::async_runtime::block_on(spawn_sidecar_standalone(port))
+}
+
+/// Run the backend on `port`, restarting it when it stops answering `/health`,
+/// and shut it down on Ctrl+C (or SIGTERM on Unix).
+pub async fn spawn_sidecar_standalone(port: u16) -> Result<(), Box<dyn Error>> {
+    let backend = BackendClient::new(None, &ProxyConfig::default())?.proxy(port);
+    let watchdog = WatchdogConfig::default();
+    let interval = Duration::from_millis(watchdog.watchdog_interval_ms);
+
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+    let mut child = spawn(port)?;
+    let mut failures = 0;
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => break,
+            status = child.wait() => {
+                return Err(format!("backend exited with {}", status?).into());
+            }
+            _ = tokio::time::sleep(interval) => {}
+        }
+
+        match backend.health().await {
+            Ok(()) => failures = 0,
+            Err(_) => failures += 1,
+        }
+        if failures >= watchdog.watchdog_threshold {
+            log::error!("Backend missed {} health checks in a row", failures);
+            if watchdog.watchdog_restart {
+                child.kill().await?;
+                child = spawn(port)?;
+            }
+            failures = 0;
+        }
+    }
+
+    log::info!("Shutting down backend");
+    let exited = async {
+        let _ = child.wait().await;
+    };
+    if !sidecar::graceful_shutdown(&backend, exited, &ShutdownConfig::default()).await {
+        child.kill().await?;
+    }
+    Ok(())
+}
+
+fn spawn(port: u16) -> std::io::Result<Child> {
+    let config = SidecarConfig::default();
+    let mut command = Command::new(binary_path(&config.name)?);
+    command
+        .args(["--port", &port.to_string()])
+        .args(&config.extra_args)
+        .kill_on_drop(true);
+    if let Some(locale) = &config.locale {
+        command.args(["--locale", locale]);
+    }
+    log::info!("Starting backend on port {}", port);
+    command.spawn()
+}
+
+/// Sidecars are installed next to the app executable.
+fn binary_path(name: &str) -> std::io::Result<PathBuf> {
+    let exe = std::env::current_exe()?;
+    let dir = exe
+        .parent()
+        .ok_or_else(|| std::io::Error::other("executable has no parent directory"))?;
+    Ok(dir.join(format!("{}{}", name, std::env::consts::EXE_SUFFIX)))
+}
+
+/// The log plugin needs a running app, so headless mode logs to stderr itself.
+struct StderrLogger;
+
+impl log::Log for StderrLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("[{}] {}", record.level(), record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
@@ -0,0 +1,71 @@
+//! UI strings for the frontend, bundled as `locales/{locale}.json` resources.
+//! Each file is a flat map of dotted keys, e.g. `"home.settings": "Settings"`,
+//! holding the same strings as `src/translations.ts` in the frontend.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Manager, State};
+
+use crate::error::EchoError;
+use crate::sidecar::is_valid_locale;
+
+const FALLBACK_LOCALE: &str = "en";
+
+/// Parsed locale files by locale code.
+#[derive(Default)]
+pub struct TranslationCache(Mutex<HashMap<String, HashMap<String, String>>>);
+
+fn locales_dir(app: &AppHandle) -> Result<PathBuf, EchoError> {
+    app.path()
+        .resolve("locales", BaseDirectory::Resource)
+        .map_err(|e| format!("failed to resolve locales directory: {}", e).into())
+}
+
+/// Strings for `locale`, falling back to its language (`zh-CN` to `zh`) and
+/// then to English when there is no bundle for it.
+#[tauri::command]
+pub fn get_translations(
+    locale: String,
+    app: AppHandle,
+    cache: State<'_, TranslationCache>,
+) -> Result<HashMap<String, String>, EchoError> {
+    if !is_valid_locale(&locale) {
+        return Err(EchoError::invalid(format!("invalid locale {:?}", locale)));
+    }
+    let available = list_available_locales(app.clone())?;
+    let language = locale.split('-').next().unwrap_or_default();
+    let chosen = [locale.as_str(), language, FALLBACK_LOCALE]
+        .into_iter()
+        .find(|candidate| available.iter().any(|a| a == candidate))
+        .ok_or("no translations are bundled")?;
+
+    let mut cache = cache.0.lock().unwrap();
+    if let Some(strings) = cache.get(chosen) {
+        return Ok(strings.clone());
+    }
+    let path = locales_dir(&app)?.join(format!("{}.json", chosen));
+    let text = std::fs::read_to_string(&path)
+        .map_err(|e| EchoError::io(format!("failed to read {}: {}", path.display(), e)))?;
+    let strings: HashMap<String, String> = serde_json::from_str(&text)?;
+    cache.insert(chosen.to_string(), strings.clone());
+    Ok(strings)
+}
+
+/// Locale codes with a bundled translation file, sorted.
+#[tauri::command]
+pub fn list_available_locales(app: AppHandle) -> Result<Vec<String>, EchoError> {
+    let dir = locales_dir(&app)?;
+    let entries = std::fs::read_dir(&dir)
+        .map_err(|e| EchoError::io(format!("failed to read {}: {}", dir.display(), e)))?;
+    let mut locales: Vec<String> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+        .collect();
+    locales.sort();
+    Ok(locales)
+}
@@ -1,110 +1,441 @@
-use std::sync::Mutex;
 use tauri::{Emitter, Manager};
-use tauri_plugin_shell::ShellExt;
+use tokio::sync::Mutex;
 
-struct SidecarState {
-    child: Option<tauri_plugin_shell::process::CommandChild>,
-}
+mod app_config;
+mod app_data;
+#[cfg(feature = "audit_log")]
+mod audit;
+#[cfg(desktop)]
+mod autostart;
+mod backend_proxy;
+mod clipboard;
+#[cfg(feature = "clipboard_history")]
+mod clipboard_history;
+mod command_queue;
+mod config_watch;
+mod connections;
+#[cfg(desktop)]
+mod crash;
+#[cfg(desktop)]
+mod crypto;
+mod deep_link;
+#[cfg(debug_assertions)]
+mod dev_commands;
+mod diagnostics;
+mod dialog;
+mod downloads;
+mod error;
+mod feedback;
+mod file_drop;
+mod file_hash;
+#[cfg(desktop)]
+mod headless;
+mod i18n;
+mod links;
+mod locale;
+mod logs;
+mod metrics;
+mod migration;
+#[cfg(desktop)]
+mod native_messaging;
+mod network;
+mod notifications;
+mod permissions;
+mod port;
+#[cfg(desktop)]
+mod power;
+#[cfg(desktop)]
+mod priority;
+mod progress;
+mod recent_files;
+mod registry;
+mod report;
+#[cfg(desktop)]
+mod screenshot;
+mod settings_archive;
+mod share;
+#[cfg(desktop)]
+mod shortcuts;
+mod sidecar;
+mod signals;
+mod startup;
+mod system;
+mod tasks;
+mod temp_files;
+mod theme;
+mod tls;
+#[cfg(desktop)]
+mod tray;
+#[cfg(desktop)]
+mod updates;
+mod watchdog;
+mod windows;
+mod ws;
+
+use app_config::AppConfig;
+use backend_proxy::{BackendClient, ProxyConfig};
+use command_queue::CommandQueue;
+use feedback::ReportRateLimit;
+use i18n::TranslationCache;
+use logs::{LogBuffer, LogFileWatcher};
+use network::NetworkCache;
+use port::PortState;
+use progress::ProgressSubscriptions;
+use recent_files::RecentFiles;
+use registry::SidecarRegistry;
+use sidecar::{BackendVersion, SidecarConfig, SidecarEnv, SidecarState};
+use system::SystemInfoCache;
+use temp_files::TempFileRegistry;
+use windows::MultiWindowSidecarGuard;
+
+/// Store file in the app data directory holding values kept across launches.
+const STORE_FILE: &str = "echo.json";
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    tauri::Builder::default()
+    let startup_timer = startup::StartupTimer::start();
+    #[cfg(desktop)]
+    if headless::is_requested() {
+        if let Err(e) = headless::run() {
+            eprintln!("Headless backend failed: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let handler: fn(tauri::ipc::Invoke) -> bool = tauri::generate_handler![
+        sidecar::check_backend_health,
+        backend_proxy::proxy_request,
+        backend_proxy::ping_backend,
+        backend_proxy::benchmark_backend,
+        backend_proxy::set_remote_backend,
+        backend_proxy::test_backend_connection,
+        backend_proxy::set_proxy,
+        connections::get_active_connections,
+        ws::connect_ws,
+        ws::send_ws_message,
+        sidecar::set_startup_timeout,
+        sidecar::restart_sidecar,
+        sidecar::get_sidecar_status,
+        sidecar::set_sidecar_env,
+        sidecar::set_locale,
+        locale::get_locale_info,
+        sidecar::send_to_sidecar,
+        sidecar::quit_app,
+        registry::spawn_sidecar,
+        registry::kill_sidecar,
+        registry::list_sidecars,
+        sidecar::get_app_version,
+        tls::rotate_tls_cert,
+        logs::get_backend_logs,
+        logs::export_logs,
+        logs::tail_log_file,
+        logs::watch_log_file,
+        port::get_port,
+        metrics::get_sidecar_metrics,
+        network::get_network_interfaces,
+        feedback::send_feedback,
+        system::get_system_info,
+        app_config::read_config,
+        app_config::write_config,
+        settings_archive::export_settings,
+        settings_archive::import_settings,
+        app_data::clear_app_data,
+        app_data::open_config_dir,
+        app_data::get_disk_usage,
+        app_data::clear_cache,
+        clipboard::read_clipboard,
+        clipboard::write_clipboard,
+        clipboard::import_from_clipboard,
+        share::share_via_os,
+        #[cfg(feature = "clipboard_history")]
+        clipboard_history::get_clipboard_history,
+        #[cfg(feature = "clipboard_history")]
+        clipboard_history::clear_clipboard_history,
+        #[cfg(feature = "clipboard_history")]
+        clipboard_history::delete_clipboard_entry,
+        downloads::download_file,
+        downloads::cancel_download,
+        file_hash::compute_file_hash,
+        #[cfg(desktop)]
+        crypto::generate_key,
+        #[cfg(desktop)]
+        crypto::delete_key,
+        #[cfg(desktop)]
+        crypto::encrypt_data,
+        #[cfg(desktop)]
+        crypto::decrypt_data,
+        dialog::pick_file,
+        dialog::pick_directory,
+        dialog::save_file,
+        dialog::pick_files_to_import,
+        progress::subscribe_progress,
+        progress::unsubscribe_progress,
+        links::open_url,
+        i18n::get_translations,
+        i18n::list_available_locales,
+        recent_files::get_recent_files,
+        recent_files::add_recent_file,
+        recent_files::clear_recent_files,
+        #[cfg(desktop)]
+        crash::get_crash_report,
+        migration::run_backend_migration,
+        diagnostics::ping,
+        diagnostics::get_state_snapshot,
+        diagnostics::run_diagnostic,
+        diagnostics::get_app_uptime,
+        diagnostics::get_sidecar_uptime,
+        diagnostics::get_session_id,
+        report::generate_report,
+        report::generate_report_to_file,
+        #[cfg(feature = "audit_log")]
+        audit::get_audit_log,
+        #[cfg(feature = "audit_log")]
+        audit::export_audit_log,
+        diagnostics::get_sidecar_binary_info,
+        startup::get_startup_timing,
+        notifications::show_notification,
+        permissions::check_permissions,
+        permissions::request_permission,
+        #[cfg(desktop)]
+        priority::set_backend_priority,
+        #[cfg(desktop)]
+        power::get_battery_status,
+        #[cfg(desktop)]
+        power::set_power_save_mode,
+        tasks::schedule_task,
+        tasks::cancel_task,
+        tasks::pause_task,
+        tasks::resume_task,
+        tasks::list_tasks,
+        temp_files::write_temp_file,
+        temp_files::cleanup_temp_files,
+        theme::apply_theme,
+        windows::set_window_title,
+        windows::resize_window,
+        windows::reload_webview,
+        windows::set_badge_count,
+        windows::clear_badge,
+        windows::focus_window,
+        #[cfg(desktop)]
+        windows::set_always_on_top,
+        windows::hide_window,
+        #[cfg(desktop)]
+        windows::minimize_to_tray,
+        #[cfg(desktop)]
+        windows::set_minimize_to_tray,
+        #[cfg(desktop)]
+        windows::reset_window_state,
+        #[cfg(desktop)]
+        autostart::set_autostart,
+        #[cfg(desktop)]
+        autostart::is_autostart_enabled,
+        #[cfg(desktop)]
+        screenshot::capture_screenshot,
+        #[cfg(desktop)]
+        native_messaging::install_native_messaging_host,
+        #[cfg(desktop)]
+        native_messaging::uninstall_native_messaging_host,
+        #[cfg(desktop)]
+        shortcuts::register_global_shortcut,
+        #[cfg(desktop)]
+        shortcuts::unregister_global_shortcut,
+        #[cfg(desktop)]
+        shortcuts::get_registered_shortcuts,
+        #[cfg(desktop)]
+        updates::update_check,
+        #[cfg(desktop)]
+        updates::apply_update,
+        #[cfg(desktop)]
+        updates::trigger_update_check,
+        #[cfg(debug_assertions)]
+        dev_commands::open_devtools,
+        #[cfg(debug_assertions)]
+        dev_commands::close_devtools
+    ];
+    #[cfg(feature = "audit_log")]
+    let handler = audit::wrap(handler);
+
+    // The theme plugin needs to come before `show_main_plugin`
+    let builder = tauri::Builder::default().plugin(theme::plugin());
+    #[cfg(feature = "audit_log")]
+    let builder = builder.manage(audit::AuditLog::default());
+    // Needs to come before the shell plugin, see `single_instance_plugin`
+    #[cfg(desktop)]
+    let builder = builder
+        .plugin(windows::single_instance_plugin())
+        .plugin(windows::window_state_plugin())
+        .plugin(windows::show_main_plugin())
+        .plugin(windows::always_on_top_plugin())
+        .plugin(autostart::plugin())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .manage(shortcuts::GlobalShortcutRegistry::default())
+        .manage(crash::CrashReportState::default())
+        .on_page_load(autostart::prompt_on_first_launch);
+
+    #[cfg(mobile)]
+    let builder = builder.plugin(tauri_plugin_sharesheet::init());
+
+    builder
         .plugin(tauri_plugin_process::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_shell::init())
-        .manage(Mutex::new(SidecarState { child: None }))
+        .plugin(tauri_plugin_store::Builder::new().build())
+        .plugin(tauri_plugin_fs::init())
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_deep_link::init())
+        .plugin(windows::guard_plugin())
+        .plugin(startup::plugin())
+        .manage(startup_timer)
+        .manage(Mutex::new(SidecarState::new()))
+        .manage(Mutex::new(PortState::default()))
+        .manage(Mutex::new(SidecarRegistry::default()))
+        .manage(Mutex::new(BackendVersion::default()))
+        .manage(Mutex::new(ProgressSubscriptions::default()))
+        .manage(Mutex::new(downloads::Downloads::default()))
+        .manage(LogBuffer::default())
+        .manage(TranslationCache::default())
+        .manage(locale::LocaleInfoCache::default())
+        .manage(LogFileWatcher::default())
+        .manage(NetworkCache::default())
+        .manage(connections::ConnectionCache::default())
+        .manage(ReportRateLimit::default())
+        .manage(SystemInfoCache::default())
+        .manage(TempFileRegistry::default())
+        .manage(ws::WsState::default())
+        .manage(MultiWindowSidecarGuard::default())
+        .invoke_handler(handler)
         .setup(|app| {
             if cfg!(debug_assertions) {
                 app.handle().plugin(
+                    // Filtered by `log::set_max_level` below, so the
+                    // configured level can be changed at runtime
                     tauri_plugin_log::Builder::default()
-                        .level(log::LevelFilter::Info)
+                        .level(log::LevelFilter::Trace)
                         .build(),
                 )?;
             }
 
-            let handle = app.handle().clone();
-
-            // Find an available port
-            let port = find_available_port(8000, 10);
-            log::info!("Using port {} for backend sidecar", port);
-
-            // Spawn the sidecar
-            let sidecar = handle
-                .shell()
-                .sidecar("echo-backend")
-                .expect("failed to create sidecar command")
-                .args(["--port", &port.to_string(), "--tauri"]);
+            #[cfg(desktop)]
+            tray::setup_tray(app)?;
+            deep_link::setup_deep_links(app);
 
-            let (mut rx, child) = sidecar.spawn().expect("failed to spawn sidecar");
-
-            // Store the child process for cleanup
-            let state = handle.state::<Mutex<SidecarState>>();
-            state.lock().unwrap().child = Some(child);
+            let handle = app.handle().clone();
+            let config = AppConfig::load(&handle);
+            match config.log_level() {
+                Ok(level) => log::set_max_level(level),
+                Err(e) => log::warn!("{}", e),
+            }
+            app.manage(Mutex::new(config.startup.clone()));
+            app.manage(config.shutdown.clone());
+            app.manage(config.watchdog.clone());
+            app.manage(port::PreferredPort(config.preferred_port));
+            let mut sidecar_env = SidecarEnv::load(&handle);
+            sidecar_env.0.extend(config.env.clone());
+            let mut sidecar_config = SidecarConfig::load(&handle);
+            sidecar_config.tls = config.tls;
+            app.manage(Mutex::new(config));
+            let root_cert = if sidecar_config.tls {
+                Some(tls::ensure(&handle)?)
+            } else {
+                None
+            };
+            let client = BackendClient::new(root_cert.as_deref(), &ProxyConfig::load(&handle))?;
+            client.restore_remote(&handle);
+            app.manage(client);
+            app.manage(Mutex::new(sidecar_config));
+            app.manage(Mutex::new(sidecar_env));
+            app.manage(RecentFiles::load(&handle));
+            #[cfg(feature = "clipboard_history")]
+            {
+                app.manage(clipboard_history::ClipboardHistory::load(&handle));
+                clipboard_history::start_watcher(handle.clone());
+            }
+            app.manage(Mutex::new(tauri::async_runtime::block_on(
+                tasks::TaskScheduler::load(&handle),
+            )?));
+            app.manage(Mutex::new(CommandQueue::new(sidecar::load_backend_config(
+                &handle,
+            ))));
 
-            // Log sidecar output in background
-            tauri::async_runtime::spawn(async move {
-                use tauri_plugin_shell::process::CommandEvent;
-                while let Some(event) = rx.recv().await {
-                    match event {
-                        CommandEvent::Stdout(line) => {
-                            let line = String::from_utf8_lossy(&line);
-                            log::info!("[sidecar] {}", line);
-                        }
-                        CommandEvent::Stderr(line) => {
-                            let line = String::from_utf8_lossy(&line);
-                            log::warn!("[sidecar] {}", line);
-                        }
-                        CommandEvent::Terminated(status) => {
-                            log::info!("[sidecar] terminated with status: {:?}", status);
-                            break;
-                        }
-                        _ => {}
-                    }
-                }
-            });
+            // Mobile can't run the sidecar, so only a remote backend (`set_remote_backend`) works
+            #[cfg(mobile)]
+            {
+                handle
+                    .state::<Mutex<SidecarState>>()
+                    .blocking_lock()
+                    .last_error = Some("not supported on mobile".into());
+                let _ = handle.emit("backend-mode", "remote");
+            }
+            #[cfg(desktop)]
+            {
+                tauri::async_runtime::block_on(registry::spawn_default(&handle))?;
+                startup::mark(&handle, startup::Phase::SidecarSpawned);
+                let _ = handle.emit("backend-mode", "local");
+            }
+            metrics::start_sampler(handle.clone());
+            locale::start_watcher(handle.clone());
+            signals::exit_on_shutdown_signal(handle.clone());
 
-            // Poll health endpoint until backend is ready
-            let poll_handle = handle.clone();
-            tauri::async_runtime::spawn(async move {
-                let url = format!("http://127.0.0.1:{}/health", port);
-                let client = reqwest::Client::new();
+            #[cfg(desktop)]
+            {
+                let config: updates::UpdateConfig = sidecar::load_backend_config(&handle);
+                let interval = std::time::Duration::from_millis(config.update_check_interval_ms);
+                app.manage(updates::start_periodic_checks(handle.clone(), interval));
+                power::start_monitor(handle.clone());
+                shortcuts::register_import_shortcut(&handle);
+            }
 
-                for i in 0..60 {
-                    match client.get(&url).send().await {
-                        Ok(resp) if resp.status().is_success() => {
-                            log::info!("Backend ready on port {} (attempt {})", port, i + 1);
-                            let _ = poll_handle.emit("backend-ready", port);
-                            return;
-                        }
-                        _ => {
-                            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-                        }
-                    }
+            match config_watch::start(&handle) {
+                Ok(watcher) => {
+                    app.manage(watcher);
                 }
-                log::error!("Backend failed to start within 30 seconds");
-            });
+                Err(e) => log::warn!("Config hot-reload disabled: {}", e),
+            }
 
+            let session = uuid::Uuid::new_v4();
+            log::info!("Session {}", session);
+            app.manage(diagnostics::SessionId(session));
+            app.manage(diagnostics::AppStartTime(std::time::Instant::now()));
             Ok(())
         })
-        .on_window_event(|window, event| {
-            if let tauri::WindowEvent::Destroyed = event {
-                let state = window.state::<Mutex<SidecarState>>();
-                let mut guard = state.lock().unwrap();
-                if let Some(child) = guard.child.take() {
-                    log::info!("Killing sidecar process on window close");
-                    let _ = child.kill();
-                }
+        .on_window_event(|window, event| match event {
+            #[cfg(desktop)]
+            tauri::WindowEvent::CloseRequested { api, .. }
+                if window.label() == "main"
+                    && windows::saved_minimize_to_tray(window.app_handle()) =>
+            {
+                api.prevent_close();
+                let (window, handle) = (window.clone(), window.app_handle().clone());
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = windows::minimize_to_tray(window, handle).await {
+                        log::warn!("Failed to minimize to tray: {}", e);
+                    }
+                });
+            }
+            #[cfg(desktop)]
+            tauri::WindowEvent::Resized(_) | tauri::WindowEvent::Moved(_) => {
+                windows::save_geometry(window, event);
             }
+            tauri::WindowEvent::DragDrop(tauri::DragDropEvent::Drop { paths, .. }) => {
+                file_drop::handle_drop(window.app_handle(), paths.clone());
+            }
+            tauri::WindowEvent::Destroyed => windows::on_window_destroyed(window, |handle| {
+                #[cfg(desktop)]
+                handle.state::<updates::PeriodicUpdateCheck>().0.cancel();
+                log::info!("Shutting down sidecar process on last window close");
+                tauri::async_runtime::block_on(sidecar::shutdown(handle));
+            }),
+            _ => {}
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
-}
-
-fn find_available_port(start: u16, attempts: u16) -> u16 {
-    for port in start..start + attempts {
-        if std::net::TcpListener::bind(("127.0.0.1", port)).is_ok() {
-            return port;
-        }
-    }
-    start
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                temp_files::cleanup(app);
+            }
+        });
 }
@@ -0,0 +1,35 @@
+use tauri::{AppHandle, Url, Window};
+use tauri_plugin_opener::OpenerExt;
+
+use crate::error::EchoError;
+
+/// The only places `open_url` will send the user. Each ends in `/` so a
+/// lookalike host such as `github.com.evil.example` can't match.
+const ALLOWED_URL_PREFIXES: &[&str] = &[
+    "https://github.com/Mtrya/echo/",
+    "https://gitee.com/KaUpane/echo/",
+    "https://dashscope.aliyun.com/",
+];
+
+/// Open an allow-listed URL in the default browser.
+#[tauri::command]
+pub fn open_url(url: String, window: Window, app: AppHandle) -> Result<(), EchoError> {
+    // Parse first so the check runs on the normalized form
+    let parsed = Url::parse(&url).map_err(|e| EchoError::invalid(format!("invalid URL: {}", e)))?;
+    if !ALLOWED_URL_PREFIXES
+        .iter()
+        .any(|prefix| parsed.as_str().starts_with(prefix))
+    {
+        log::warn!(
+            "Refusing to open {} requested by window {}",
+            parsed,
+            window.label()
+        );
+        return Err(EchoError::Unauthorized);
+    }
+
+    log::info!("Opening {} for window {}", parsed, window.label());
+    app.opener()
+        .open_url(parsed.as_str(), None::<&str>)
+        .map_err(|e| format!("failed to open {}: {}", parsed, e).into())
+}
@@ -0,0 +1,217 @@
+//! Number and date conventions of the OS locale, for formatting that matches
+//! the rest of the desktop. Only the locale code is read from the OS, so the
+//! details come from its language and region rather than user overrides.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+const FALLBACK_LOCALE: &str = "en-US";
+/// None of the platforms notify about locale changes in a way that's
+/// reachable from here, so the OS locale is checked this often.
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Regions whose week starts on Sunday; Saturday for `SATURDAY_FIRST`,
+/// Monday everywhere else.
+const SUNDAY_FIRST: &[&str] = &[
+    "US", "CA", "MX", "BR", "JP", "KR", "TW", "HK", "IL", "IN", "PH", "SA", "ZA",
+];
+const SATURDAY_FIRST: &[&str] = &[
+    "AE", "AF", "BH", "DZ", "EG", "IQ", "IR", "JO", "KW", "LY", "OM", "QA", "SY",
+];
+/// Regions with 12-hour clocks.
+const TWELVE_HOUR: &[&str] = &["US", "CA", "AU", "NZ", "IN", "PH", "EG", "SA"];
+
+#[derive(Clone, Serialize)]
+pub struct LocaleInfo {
+    pub locale: String,
+    pub decimal_separator: String,
+    pub thousands_separator: String,
+    /// Unicode (CLDR) pattern, e.g. `dd.MM.yyyy`.
+    pub date_format: String,
+    pub time_format: String,
+    /// 0 is Sunday, 1 Monday and 6 Saturday.
+    pub first_day_of_week: u8,
+}
+
+impl LocaleInfo {
+    fn for_locale(locale: &str) -> Self {
+        let mut parts = locale.split('-');
+        let language = parts.next().unwrap_or_default().to_lowercase();
+        let region = parts
+            .find(|part| part.len() == 2)
+            .map(str::to_uppercase)
+            .unwrap_or_else(|| default_region(&language).into());
+        let (language, region) = (language.as_str(), region.as_str());
+
+        let (decimal, thousands) = match (language, region) {
+            ("de", "CH") => (".", "’"),
+            ("es", "MX") => (".", ","),
+            ("fr", _) => (",", "\u{202f}"),
+            ("ru" | "pl" | "cs" | "sk" | "uk" | "sv" | "fi" | "nb" | "hu", _) => (",", "\u{a0}"),
+            ("de" | "es" | "it" | "pt" | "nl" | "da" | "id" | "tr" | "el" | "ro", _) => (",", "."),
+            _ => (".", ","),
+        };
+        let date_format = match (language, region) {
+            (_, "US") => "M/d/yyyy",
+            ("zh", _) => "yyyy/M/d",
+            ("ja", _) => "yyyy/MM/dd",
+            ("ko", _) => "yyyy. M. d.",
+            ("sv" | "lt" | "hu", _) | ("en", "CA") => "yyyy-MM-dd",
+            ("de" | "ru" | "pl" | "cs" | "sk" | "uk" | "fi" | "nb" | "da" | "tr" | "ro", _) => {
+                "dd.MM.yyyy"
+            }
+            ("nl", _) => "dd-MM-yyyy",
+            _ => "dd/MM/yyyy",
+        };
+        let time_format = if TWELVE_HOUR.contains(&region) {
+            "h:mm a"
+        } else {
+            "HH:mm"
+        };
+        let first_day_of_week = if SUNDAY_FIRST.contains(&region) {
+            0
+        } else if SATURDAY_FIRST.contains(&region) {
+            6
+        } else {
+            1
+        };
+
+        Self {
+            locale: locale.into(),
+            decimal_separator: decimal.into(),
+            thousands_separator: thousands.into(),
+            date_format: date_format.into(),
+            time_format: time_format.into(),
+            first_day_of_week,
+        }
+    }
+}
+
+/// Where a language is mostly used, for locales given without a region.
+fn default_region(language: &str) -> &'static str {
+    match language {
+        "en" => "US",
+        "zh" => "CN",
+        "ja" => "JP",
+        "ko" => "KR",
+        "pt" => "BR",
+        "ar" => "EG",
+        _ => "",
+    }
+}
+
+fn os_locale() -> String {
+    sys_locale::get_locale()
+        .and_then(|locale| normalize(&locale))
+        .unwrap_or_else(|| FALLBACK_LOCALE.into())
+}
+
+/// `en_US.UTF-8` style codes from some Linux setups normalized to `en-US`.
+fn normalize(locale: &str) -> Option<String> {
+    let locale = locale.split(['.', '@']).next()?.replace('_', "-");
+    (!locale.is_empty()).then_some(locale)
+}
+
+/// `LocaleInfo` for the OS locale, until it changes.
+#[derive(Default)]
+pub struct LocaleInfoCache(Mutex<Option<LocaleInfo>>);
+
+#[tauri::command]
+pub fn get_locale_info(cache: State<'_, LocaleInfoCache>) -> LocaleInfo {
+    cache
+        .0
+        .lock()
+        .unwrap()
+        .get_or_insert_with(|| LocaleInfo::for_locale(&os_locale()))
+        .clone()
+}
+
+/// Emit `locale-changed` with the new `LocaleInfo` when the OS locale changes.
+pub fn start_watcher(handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut last = os_locale();
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            let current = os_locale();
+            if current == last {
+                continue;
+            }
+            log::info!("OS locale changed from {} to {}", last, current);
+            let info = LocaleInfo::for_locale(&current);
+            *handle.state::<LocaleInfoCache>().0.lock().unwrap() = Some(info.clone());
+            let _ = handle.emit("locale-changed", info);
+            last = current;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn os_locale_codes_are_normalized() {
+        assert_eq!(normalize("en_US.UTF-8").as_deref(), Some("en-US"));
+        assert_eq!(normalize("de_DE@euro").as_deref(), Some("de-DE"));
+        assert_eq!(normalize("fr-FR").as_deref(), Some("fr-FR"));
+        assert_eq!(normalize(".UTF-8"), None);
+        assert_eq!(normalize(""), None);
+    }
+
+    #[test]
+    fn us_english() {
+        let info = LocaleInfo::for_locale("en-US");
+        assert_eq!(info.locale, "en-US");
+        assert_eq!(info.decimal_separator, ".");
+        assert_eq!(info.thousands_separator, ",");
+        assert_eq!(info.date_format, "M/d/yyyy");
+        assert_eq!(info.time_format, "h:mm a");
+        assert_eq!(info.first_day_of_week, 0);
+    }
+
+    #[test]
+    fn german() {
+        let info = LocaleInfo::for_locale("de-DE");
+        assert_eq!(info.decimal_separator, ",");
+        assert_eq!(info.thousands_separator, ".");
+        assert_eq!(info.date_format, "dd.MM.yyyy");
+        assert_eq!(info.time_format, "HH:mm");
+        assert_eq!(info.first_day_of_week, 1);
+
+        let swiss = LocaleInfo::for_locale("de-CH");
+        assert_eq!(swiss.decimal_separator, ".");
+        assert_eq!(swiss.thousands_separator, "’");
+    }
+
+    #[test]
+    fn region_is_found_after_a_script_and_case_is_ignored() {
+        let info = LocaleInfo::for_locale("zh-Hant-tw");
+        assert_eq!(info.date_format, "yyyy/M/d");
+        assert_eq!(info.first_day_of_week, 0);
+
+        let info = LocaleInfo::for_locale("EN-ca");
+        assert_eq!(info.date_format, "yyyy-MM-dd");
+        assert_eq!(info.time_format, "h:mm a");
+    }
+
+    #[test]
+    fn missing_region_falls_back_to_the_language_default() {
+        let en = LocaleInfo::for_locale("en");
+        assert_eq!(en.date_format, "M/d/yyyy");
+        assert_eq!(en.first_day_of_week, 0);
+
+        let ar = LocaleInfo::for_locale("ar");
+        assert_eq!(ar.time_format, "h:mm a");
+        assert_eq!(ar.first_day_of_week, 6);
+
+        let unknown = LocaleInfo::for_locale("xx");
+        assert_eq!(unknown.decimal_separator, ".");
+        assert_eq!(unknown.date_format, "dd/MM/yyyy");
+        assert_eq!(unknown.time_format, "HH:mm");
+        assert_eq!(unknown.first_day_of_week, 1);
+    }
+}
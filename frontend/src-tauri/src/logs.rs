@@ -0,0 +1,256 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::dialog;
+use crate::error::EchoError;
+use crate::file_drop::resolve_within;
+
+/// How much of a log file `tail_log_file` reads at a time, from the end.
+const TAIL_CHUNK: u64 = 8 * 1024;
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Info,
+    Warn,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LogEntry {
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: u64,
+    pub level: LogLevel,
+    pub message: String,
+}
+
+/// Most recent sidecar output lines, oldest first.
+pub struct LogBuffer {
+    entries: Mutex<VecDeque<LogEntry>>,
+    capacity: usize,
+}
+
+impl Default for LogBuffer {
+    fn default() -> Self {
+        Self::new(1000)
+    }
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+        }
+    }
+
+    pub fn push(&self, entry: LogEntry) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// Every buffered entry, oldest first.
+    pub fn all(&self) -> Vec<LogEntry> {
+        self.entries.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// The last `limit` entries, oldest first.
+    pub fn recent(&self, limit: usize) -> Vec<LogEntry> {
+        let entries = self.entries.lock().unwrap();
+        let skip = entries.len().saturating_sub(limit);
+        entries.iter().skip(skip).cloned().collect()
+    }
+}
+
+pub fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Buffer a line of sidecar output and forward it to live `backend-log` listeners.
+pub fn record(handle: &AppHandle, level: LogLevel, message: &str) {
+    let entry = LogEntry {
+        timestamp: now_ms(),
+        level,
+        message: message.trim_end().to_string(),
+    };
+    let _ = handle.emit("backend-log", &entry);
+    handle.state::<LogBuffer>().push(entry);
+}
+
+#[tauri::command]
+pub fn get_backend_logs(limit: usize, logs: State<'_, LogBuffer>) -> Vec<LogEntry> {
+    logs.recent(limit)
+}
+
+/// Save the log buffer as newline-delimited JSON, after a header line with
+/// the export time, app version and platform. `path` must have been chosen
+/// with `save_file` or inside a folder from `pick_directory`.
+#[tauri::command]
+pub fn export_logs(
+    path: String,
+    app: AppHandle,
+    logs: State<'_, LogBuffer>,
+) -> Result<(), EchoError> {
+    let path = PathBuf::from(path);
+    if !dialog::is_picked(&app, &path) {
+        log::warn!("Refusing to export logs to {}", path.display());
+        return Err(EchoError::Unauthorized);
+    }
+
+    let header = json!({
+        "exported_at": now_ms(),
+        "version": app.package_info().version.to_string(),
+        "platform": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+    });
+    write_ndjson(&path, &header, &logs.all()).map_err(|e| match e.kind() {
+        io::ErrorKind::PermissionDenied => EchoError::io(format!(
+            "Permission denied writing logs to {}",
+            path.display()
+        )),
+        _ => EchoError::io(format!("Failed to write logs to {}: {}", path.display(), e)),
+    })
+}
+
+fn write_ndjson(path: &Path, header: &serde_json::Value, entries: &[LogEntry]) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    serde_json::to_writer(&mut out, header)?;
+    writeln!(out)?;
+    for entry in entries {
+        serde_json::to_writer(&mut out, entry)?;
+        writeln!(out)?;
+    }
+    out.flush()
+}
+
+/// The log file `watch_log_file` is watching, replaced by the next call.
+#[derive(Default)]
+pub struct LogFileWatcher(Mutex<Option<RecommendedWatcher>>);
+
+#[derive(Clone, Serialize)]
+struct LogFileChanged {
+    path: PathBuf,
+}
+
+/// Resolve `path` and make sure it is inside the app log directory.
+fn log_file_path(app: &AppHandle, path: &str) -> Result<PathBuf, EchoError> {
+    let dir = app
+        .path()
+        .app_log_dir()
+        .ok()
+        .and_then(|dir| dir.canonicalize().ok())
+        .ok_or("could not resolve the log directory")?;
+    resolve_within(&dir, Path::new(path))
+        .map_err(|_| EchoError::invalid(format!("{} is outside the log directory", path)))
+}
+
+/// The last `lines` lines of a file in the app log directory, oldest first.
+#[tauri::command]
+pub fn tail_log_file(path: String, lines: usize, app: AppHandle) -> Result<Vec<String>, EchoError> {
+    let path = log_file_path(&app, &path)?;
+    tail(&path, lines)
+        .map_err(|e| EchoError::io(format!("failed to read {}: {}", path.display(), e)))
+}
+
+/// Read backwards from the end until the chunk holds more than `lines` newlines,
+/// so large files are never read in full.
+fn tail(path: &Path, lines: usize) -> io::Result<Vec<String>> {
+    let mut file = File::open(path)?;
+    let mut pos = file.metadata()?.len();
+    let mut buf = Vec::new();
+    while pos > 0 && buf.iter().filter(|&&b| b == b'\n').count() <= lines {
+        let read = TAIL_CHUNK.min(pos);
+        pos -= read;
+        file.seek(SeekFrom::Start(pos))?;
+        let mut chunk = vec![0; read as usize];
+        file.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&buf);
+        buf = chunk;
+    }
+
+    let text = String::from_utf8_lossy(&buf);
+    let all: Vec<&str> = text.lines().collect();
+    let skip = all.len().saturating_sub(lines);
+    Ok(all[skip..].iter().map(|line| line.to_string()).collect())
+}
+
+/// Emit `log-file-changed` whenever the file is written to. Only one file is
+/// watched at a time; calling this again switches to the new one.
+#[tauri::command]
+pub fn watch_log_file(
+    path: String,
+    handle: AppHandle,
+    watcher: State<'_, LogFileWatcher>,
+) -> Result<(), EchoError> {
+    let path = log_file_path(&handle, &path)?;
+    let changed = path.clone();
+    let mut new_watcher =
+        notify::recommended_watcher(move |res: notify::Result<notify::Event>| match res {
+            Ok(event) if matches!(event.kind, EventKind::Modify(_)) => {
+                let _ = handle.emit(
+                    "log-file-changed",
+                    LogFileChanged {
+                        path: changed.clone(),
+                    },
+                );
+            }
+            Ok(_) => {}
+            Err(e) => log::warn!("Log file watcher error: {}", e),
+        })
+        .map_err(|e| format!("failed to watch {}: {}", path.display(), e))?;
+    new_watcher
+        .watch(&path, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("failed to watch {}: {}", path.display(), e))?;
+
+    *watcher.0.lock().unwrap() = Some(new_watcher);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tail_of(contents: &str, lines: usize) -> Vec<String> {
+        let path = std::env::temp_dir().join(format!("echo-tail-{}.log", uuid::Uuid::new_v4()));
+        std::fs::write(&path, contents).unwrap();
+        let tailed = tail(&path, lines).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        tailed
+    }
+
+    #[test]
+    fn returns_the_last_lines_oldest_first() {
+        assert_eq!(tail_of("a\nb\nc\nd\n", 2), ["c", "d"]);
+        assert_eq!(tail_of("a\nb\nc\nd", 2), ["c", "d"]);
+    }
+
+    #[test]
+    fn short_and_empty_files_are_returned_whole() {
+        assert_eq!(tail_of("a\nb\n", 10), ["a", "b"]);
+        assert!(tail_of("", 10).is_empty());
+        assert!(tail_of("a\nb\n", 0).is_empty());
+    }
+
+    #[test]
+    fn lines_spanning_chunks_are_kept_intact() {
+        let lines: Vec<String> = (0..5_000).map(|i| format!("line {i}")).collect();
+        let contents = lines.join("\n") + "\n";
+        assert!(contents.len() as u64 > 2 * TAIL_CHUNK);
+
+        assert_eq!(tail_of(&contents, 3_000), lines[2_000..]);
+    }
+}
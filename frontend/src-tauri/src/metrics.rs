@@ -0,0 +1,58 @@
+use std::time::Duration;
+
+use serde::Serialize;
+use sysinfo::{Pid, ProcessesToUpdate, System};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_shell::process::CommandChild;
+use tokio::sync::Mutex;
+
+use crate::logs;
+use crate::sidecar::SidecarState;
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(30);
+/// Samples kept in `SidecarState`, one hour at the default interval.
+pub const METRICS_CAPACITY: usize = 120;
+
+#[derive(Clone, Serialize)]
+pub struct ProcessSample {
+    pub timestamp_ms: u64,
+    pub cpu_percent: f32,
+    pub memory_kb: u64,
+}
+
+/// Sample the sidecar's CPU and memory usage every `SAMPLE_INTERVAL`, keeping
+/// the history in `SidecarState` and emitting each sample as `backend-metrics`.
+pub fn start_sampler(handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut system = System::new();
+        let mut interval = tokio::time::interval(SAMPLE_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            let state = handle.state::<Mutex<SidecarState>>();
+            let Some(pid) = state.lock().await.child.as_ref().map(CommandChild::pid) else {
+                continue;
+            };
+            let pid = Pid::from_u32(pid);
+            system.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+            let Some(process) = system.process(pid) else {
+                continue;
+            };
+
+            let sample = ProcessSample {
+                timestamp_ms: logs::now_ms(),
+                cpu_percent: process.cpu_usage(),
+                memory_kb: process.memory() / 1024,
+            };
+            state.lock().await.push_metrics(sample.clone());
+            let _ = handle.emit("backend-metrics", sample);
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn get_sidecar_metrics(handle: AppHandle) -> Vec<ProcessSample> {
+    let state = handle.state::<Mutex<SidecarState>>();
+    let guard = state.lock().await;
+    guard.metrics.iter().cloned().collect()
+}
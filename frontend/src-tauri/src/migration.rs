@@ -0,0 +1,117 @@
+//! Letting the backend migrate its local data after an app update.
+//!
+//! The version the data was last migrated to is kept in the store and only
+//! advanced once `POST /migrate` succeeds, so a failed migration is retried on
+//! the next launch. A fresh install has nothing to migrate and only records
+//! the running version.
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
+use tauri_plugin_store::StoreExt;
+use tokio::sync::Mutex;
+
+use crate::backend_proxy::{BackendClient, BackendProxy};
+use crate::error::EchoError;
+use crate::port::PortState;
+use crate::sidecar;
+use crate::windows;
+use crate::STORE_FILE;
+
+const MIGRATED_VERSION_KEY: &str = "migrated_version";
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct MigrationResult {
+    pub migrated: bool,
+    pub steps: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+fn migrated_version<R: Runtime>(app: &AppHandle<R>) -> Option<String> {
+    app.store(STORE_FILE)
+        .ok()
+        .and_then(|store| store.get(MIGRATED_VERSION_KEY))
+        .and_then(|value| value.as_str().map(String::from))
+}
+
+/// Migrate from the last migrated version (`None` if there is none yet) to the
+/// running one.
+async fn migrate(app: &AppHandle, backend: &BackendProxy) -> Result<MigrationResult, EchoError> {
+    let to = app.package_info().version.to_string();
+    let result = match migrated_version(app) {
+        None => {
+            log::info!("Fresh install, recording data version {}", to);
+            MigrationResult::default()
+        }
+        Some(from) => run(backend, &from, &to).await?,
+    };
+
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    store.set(MIGRATED_VERSION_KEY, to);
+    store
+        .save()
+        .map_err(|e| format!("failed to save migrated version: {}", e))?;
+    Ok(result)
+}
+
+async fn run(backend: &BackendProxy, from: &str, to: &str) -> Result<MigrationResult, EchoError> {
+    let Some(result) = backend.migrate(Some(from), to).await? else {
+        log::info!("Backend has nothing to migrate from {} to {}", from, to);
+        return Ok(MigrationResult::default());
+    };
+    for warning in &result.warnings {
+        log::warn!("Migration: {}", warning);
+    }
+    if result.migrated {
+        log::info!(
+            "Migrated backend data from {} to {}: {}",
+            from,
+            to,
+            result.steps.join(", ")
+        );
+    }
+    Ok(result)
+}
+
+/// Whether data from an earlier version still has to be migrated. The main
+/// window stays hidden until then, see `windows::show_main_plugin`.
+pub fn pending<R: Runtime>(app: &AppHandle<R>) -> bool {
+    migrated_version(app).is_some_and(|version| version != app.package_info().version.to_string())
+}
+
+/// Run the migration if the app was updated since the last one, and show the
+/// main window once it is done. Called once the backend is ready. If it fails
+/// the window stays hidden, since the app can't work with unmigrated data,
+/// and the app quits after showing the error.
+pub async fn migrate_if_updated(app: &AppHandle, backend: &BackendProxy) {
+    if migrated_version(app).as_deref() == Some(&app.package_info().version.to_string()) {
+        return;
+    }
+    let was_pending = pending(app);
+    let Err(e) = migrate(app, backend).await else {
+        if was_pending {
+            windows::show_main(app);
+        }
+        return;
+    };
+
+    log::error!("Backend migration failed: {}", e);
+    let handle = app.clone();
+    app.dialog()
+        .message(format!(
+            "Echo could not update its data to this version and has to close.\n\n{}",
+            e
+        ))
+        .title("Update Failed")
+        .kind(MessageDialogKind::Error)
+        .show(move |_| {
+            tauri::async_runtime::spawn(sidecar::quit_app(handle));
+        });
+}
+
+#[tauri::command]
+pub async fn run_backend_migration(app: AppHandle) -> Result<MigrationResult, EchoError> {
+    let port = app.state::<Mutex<PortState>>().lock().await.0;
+    let backend = app.state::<BackendClient>().current(port)?;
+    migrate(&app, &backend).await
+}
@@ -0,0 +1,228 @@
+//! Registration of the app as a native messaging host, so a companion browser
+//! extension can launch it. Chrome and Chromium look for the host manifest in
+//! per-user locations, so no elevated privileges are needed.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::error::EchoError;
+
+/// Browser locations for per-user host manifests, relative to the home directory.
+#[cfg(target_os = "linux")]
+const MANIFEST_DIRS: &[&str] = &[
+    ".config/google-chrome/NativeMessagingHosts",
+    ".config/chromium/NativeMessagingHosts",
+];
+#[cfg(target_os = "macos")]
+const MANIFEST_DIRS: &[&str] = &[
+    "Library/Application Support/Google/Chrome/NativeMessagingHosts",
+    "Library/Application Support/Chromium/NativeMessagingHosts",
+];
+/// On Windows the manifest can live anywhere; these keys under `HKCU` point to it.
+#[cfg(windows)]
+const REGISTRY_KEYS: &[&str] = &[
+    r"Software\Google\Chrome\NativeMessagingHosts",
+    r"Software\Chromium\NativeMessagingHosts",
+];
+
+#[derive(Serialize)]
+struct HostManifest {
+    name: String,
+    description: String,
+    path: String,
+    #[serde(rename = "type")]
+    kind: &'static str,
+    allowed_origins: Vec<String>,
+}
+
+impl HostManifest {
+    fn new(name: &str, app_name: &str, launcher: &Path, extension_ids: &[String]) -> Self {
+        Self {
+            name: name.to_string(),
+            description: format!("{} native messaging host", app_name),
+            path: launcher.to_string_lossy().into_owned(),
+            kind: "stdio",
+            allowed_origins: extension_ids
+                .iter()
+                .map(|id| format!("chrome-extension://{}/", id))
+                .collect(),
+        }
+    }
+}
+
+/// The browser can't pass extra arguments to a host, so the manifest points
+/// at a launcher script that adds `--native-messaging`.
+fn write_launcher(dir: &Path) -> Result<PathBuf, EchoError> {
+    let exe = std::env::current_exe()?;
+    #[cfg(windows)]
+    let (path, script) = (
+        dir.join("native-host.bat"),
+        format!(
+            "@echo off\r\n\"{}\" --native-messaging %*\r\n",
+            exe.display()
+        ),
+    );
+    #[cfg(not(windows))]
+    let (path, script) = (
+        dir.join("native-host.sh"),
+        format!(
+            "#!/bin/sh\nexec \"{}\" --native-messaging \"$@\"\n",
+            exe.display()
+        ),
+    );
+    fs::write(&path, script).map_err(|e| format!("failed to write {}: {}", path.display(), e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(path)
+}
+
+/// Register the host for the given extension ids, e.g. `abcdefghijklmnopabcdefghijklmnop`.
+#[tauri::command]
+pub fn install_native_messaging_host(
+    app: AppHandle,
+    extension_ids: Vec<String>,
+) -> Result<(), EchoError> {
+    if extension_ids.is_empty() {
+        return Err(EchoError::invalid("at least one extension id is required"));
+    }
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join("native-messaging");
+    fs::create_dir_all(&dir)
+        .map_err(|e| EchoError::io(format!("failed to create {}: {}", dir.display(), e)))?;
+
+    let name = app.config().identifier.clone();
+    let manifest = HostManifest::new(
+        &name,
+        &app.package_info().name,
+        &write_launcher(&dir)?,
+        &extension_ids,
+    );
+    let json = serde_json::to_string_pretty(&manifest)?;
+    let file_name = format!("{}.json", name);
+
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    {
+        let home = app.path().home_dir().map_err(|e| e.to_string())?;
+        for browser_dir in MANIFEST_DIRS {
+            let browser_dir = home.join(browser_dir);
+            fs::create_dir_all(&browser_dir)
+                .and_then(|_| fs::write(browser_dir.join(&file_name), &json))
+                .map_err(|e| {
+                    EchoError::io(format!(
+                        "failed to install in {}: {}",
+                        browser_dir.display(),
+                        e
+                    ))
+                })?;
+        }
+    }
+    #[cfg(windows)]
+    {
+        use winreg::enums::HKEY_CURRENT_USER;
+        use winreg::RegKey;
+
+        let manifest_path = dir.join(&file_name);
+        fs::write(&manifest_path, &json)?;
+        let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+        for key in REGISTRY_KEYS {
+            let (key, _) = hkcu
+                .create_subkey(format!(r"{}\{}", key, name))
+                .map_err(|e| format!("failed to create registry key: {}", e))?;
+            key.set_value("", &manifest_path.to_string_lossy().into_owned())
+                .map_err(|e| format!("failed to write registry key: {}", e))?;
+        }
+    }
+
+    log::info!("Installed native messaging host {}", name);
+    Ok(())
+}
+
+/// Undo `install_native_messaging_host`; missing files are not an error.
+#[tauri::command]
+pub fn uninstall_native_messaging_host(app: AppHandle) -> Result<(), EchoError> {
+    let name = app.config().identifier.clone();
+    let file_name = format!("{}.json", name);
+
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    {
+        let home = app.path().home_dir().map_err(|e| e.to_string())?;
+        for browser_dir in MANIFEST_DIRS {
+            let path = home.join(browser_dir).join(&file_name);
+            match fs::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    return Err(EchoError::io(format!(
+                        "failed to remove {}: {}",
+                        path.display(),
+                        e
+                    )));
+                }
+                _ => {}
+            }
+        }
+    }
+    #[cfg(windows)]
+    {
+        use winreg::enums::HKEY_CURRENT_USER;
+        use winreg::RegKey;
+
+        let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+        for key in REGISTRY_KEYS {
+            match hkcu.delete_subkey_all(format!(r"{}\{}", key, name)) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    return Err(EchoError::io(format!(
+                        "failed to remove registry key: {}",
+                        e
+                    )));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    if let Ok(dir) = app.path().app_data_dir() {
+        let _ = fs::remove_dir_all(dir.join("native-messaging"));
+    }
+    log::info!("Removed native messaging host {}", name);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn manifest_matches_the_chrome_schema() {
+        let manifest = HostManifest::new(
+            "com.echo.app",
+            "Echo",
+            Path::new("/opt/echo/native-host.sh"),
+            &[
+                "abcdefghijklmnopabcdefghijklmnop".to_string(),
+                "ponmlkjihgfedcbaponmlkjihgfedcba".to_string(),
+            ],
+        );
+        assert_eq!(
+            serde_json::to_value(&manifest).unwrap(),
+            json!({
+                "name": "com.echo.app",
+                "description": "Echo native messaging host",
+                "path": "/opt/echo/native-host.sh",
+                "type": "stdio",
+                "allowed_origins": [
+                    "chrome-extension://abcdefghijklmnopabcdefghijklmnop/",
+                    "chrome-extension://ponmlkjihgfedcbaponmlkjihgfedcba/",
+                ],
+            })
+        );
+    }
+}
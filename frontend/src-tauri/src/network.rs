@@ -0,0 +1,49 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::State;
+
+use crate::error::EchoError;
+
+/// How long a listing of the network interfaces is reused.
+const CACHE_TTL: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Serialize)]
+pub struct NetworkInterface {
+    pub name: String,
+    pub ip: String,
+    pub is_loopback: bool,
+}
+
+/// When a listing was taken, and the interfaces in it.
+type Snapshot = (Instant, Vec<NetworkInterface>);
+
+/// Last interface listing, shared by every call for `CACHE_TTL`.
+#[derive(Clone, Default)]
+pub struct NetworkCache(Arc<Mutex<Option<Snapshot>>>);
+
+/// Active network interfaces, for diagnosing connection problems.
+#[tauri::command]
+pub fn get_network_interfaces(
+    cache: State<'_, NetworkCache>,
+) -> Result<Vec<NetworkInterface>, EchoError> {
+    let mut cached = cache.0.lock().unwrap();
+    if let Some((taken, interfaces)) = cached.as_ref() {
+        if taken.elapsed() < CACHE_TTL {
+            return Ok(interfaces.clone());
+        }
+    }
+
+    let interfaces: Vec<_> = if_addrs::get_if_addrs()
+        .map_err(|e| EchoError::io(format!("failed to list network interfaces: {}", e)))?
+        .into_iter()
+        .map(|interface| NetworkInterface {
+            ip: interface.ip().to_string(),
+            is_loopback: interface.is_loopback(),
+            name: interface.name,
+        })
+        .collect();
+    *cached = Some((Instant::now(), interfaces.clone()));
+    Ok(interfaces)
+}
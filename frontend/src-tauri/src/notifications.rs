@@ -0,0 +1,65 @@
+//! OS notifications, e.g. for work that finishes while the app is minimized.
+
+use tauri::plugin::PermissionState;
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+use tauri_plugin_store::StoreExt;
+
+use crate::error::EchoError;
+use crate::STORE_FILE;
+
+/// Store key for whether the user allowed notifications, so they are only asked once.
+const PERMISSION_KEY: &str = "notification_permission";
+
+/// The answer remembered by `permission_granted` or `request_permission`.
+pub fn saved_permission(app: &AppHandle) -> Option<bool> {
+    app.store(STORE_FILE)
+        .ok()?
+        .get(PERMISSION_KEY)
+        .and_then(|value| value.as_bool())
+}
+
+/// Ask for permission the first time and remember the answer.
+fn permission_granted(app: &AppHandle) -> Result<bool, EchoError> {
+    match saved_permission(app) {
+        Some(granted) => Ok(granted),
+        None => request_permission(app),
+    }
+}
+
+/// Ask the OS unless it already allows notifications, and remember the answer.
+pub fn request_permission(app: &AppHandle) -> Result<bool, EchoError> {
+    let notification = app.notification();
+    let state = match notification.permission_state() {
+        Ok(PermissionState::Granted) => PermissionState::Granted,
+        _ => notification
+            .request_permission()
+            .map_err(|e| format!("failed to request notification permission: {}", e))?,
+    };
+    let granted = state == PermissionState::Granted;
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    store.set(PERMISSION_KEY, granted);
+    store.save().map_err(|e| e.to_string())?;
+    Ok(granted)
+}
+
+/// Async so that waiting on the permission prompt doesn't block the main thread.
+#[tauri::command]
+pub async fn show_notification(
+    title: String,
+    body: String,
+    icon: Option<String>,
+    app: AppHandle,
+) -> Result<(), EchoError> {
+    if !permission_granted(&app)? {
+        return Err(EchoError::Unauthorized);
+    }
+
+    let mut builder = app.notification().builder().title(title).body(body);
+    if let Some(icon) = icon {
+        builder = builder.icon(icon);
+    }
+    builder
+        .show()
+        .map_err(|e| format!("failed to show notification: {}", e).into())
+}
@@ -0,0 +1,132 @@
+//! OS permissions the app may be missing. Full disk access and accessibility
+//! only exist on macOS and are reported as granted elsewhere.
+
+use serde::Serialize;
+use tauri::plugin::PermissionState;
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+use crate::error::EchoError;
+use crate::notifications;
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermState {
+    Granted,
+    Denied,
+    NotDetermined,
+}
+
+#[derive(Serialize)]
+pub struct PermissionStatus {
+    pub notifications: PermState,
+    pub full_disk_access: PermState,
+    pub accessibility: PermState,
+}
+
+fn notifications_state(app: &AppHandle) -> PermState {
+    match notifications::saved_permission(app) {
+        Some(true) => PermState::Granted,
+        Some(false) => PermState::Denied,
+        None => match app.notification().permission_state() {
+            Ok(PermissionState::Granted) => PermState::Granted,
+            Ok(PermissionState::Denied) => PermState::Denied,
+            _ => PermState::NotDetermined,
+        },
+    }
+}
+
+/// There is no API for this, but the TCC database can only be opened with
+/// full disk access.
+#[cfg(target_os = "macos")]
+fn full_disk_access_state(app: &AppHandle) -> PermState {
+    use tauri::Manager;
+
+    let Ok(home) = app.path().home_dir() else {
+        return PermState::NotDetermined;
+    };
+    let tcc = home.join("Library/Application Support/com.apple.TCC/TCC.db");
+    match std::fs::File::open(tcc) {
+        Ok(_) => PermState::Granted,
+        Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => PermState::Denied,
+        Err(_) => PermState::NotDetermined,
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn full_disk_access_state(_app: &AppHandle) -> PermState {
+    PermState::Granted
+}
+
+#[cfg(target_os = "macos")]
+fn accessibility_state() -> PermState {
+    #[link(name = "ApplicationServices", kind = "framework")]
+    extern "C" {
+        fn AXIsProcessTrusted() -> u8;
+    }
+    // SAFETY: takes no arguments and only reads the process's TCC entry
+    if unsafe { AXIsProcessTrusted() } != 0 {
+        PermState::Granted
+    } else {
+        PermState::Denied
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn accessibility_state() -> PermState {
+    PermState::Granted
+}
+
+#[tauri::command]
+pub fn check_permissions(app: AppHandle) -> PermissionStatus {
+    PermissionStatus {
+        notifications: notifications_state(&app),
+        full_disk_access: full_disk_access_state(&app),
+        accessibility: accessibility_state(),
+    }
+}
+
+/// macOS has no prompt for these, so send the user to the System Settings
+/// pane where they can be granted.
+#[cfg(target_os = "macos")]
+fn open_privacy_settings(app: &AppHandle, pane: &str) -> Result<(), EchoError> {
+    use tauri_plugin_opener::OpenerExt;
+
+    let url = format!(
+        "x-apple.systempreferences:com.apple.preference.security?{}",
+        pane
+    );
+    app.opener()
+        .open_url(&url, None::<&str>)
+        .map_err(|e| format!("failed to open System Settings: {}", e).into())
+}
+
+/// Ask for `capability` (`notifications`, `full_disk_access` or
+/// `accessibility`) and return its state afterwards. For the latter two that
+/// is before the user had a chance to change it in System Settings.
+#[tauri::command]
+pub async fn request_permission(
+    capability: String,
+    app: AppHandle,
+) -> Result<PermState, EchoError> {
+    match capability.as_str() {
+        "notifications" => {
+            notifications::request_permission(&app)?;
+            Ok(notifications_state(&app))
+        }
+        "full_disk_access" => {
+            #[cfg(target_os = "macos")]
+            open_privacy_settings(&app, "Privacy_AllFiles")?;
+            Ok(full_disk_access_state(&app))
+        }
+        "accessibility" => {
+            #[cfg(target_os = "macos")]
+            open_privacy_settings(&app, "Privacy_Accessibility")?;
+            Ok(accessibility_state())
+        }
+        _ => Err(EchoError::invalid(format!(
+            "unknown capability {:?}",
+            capability
+        ))),
+    }
+}
@@ -0,0 +1,259 @@
+use std::fmt;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_store::StoreExt;
+use tokio::sync::Mutex;
+
+use crate::error::EchoError;
+use crate::STORE_FILE;
+
+const PORT_KEY: &str = "backend_port";
+const PORT_RANGE_START: u16 = 8000;
+const PORT_SCAN_ATTEMPTS: u16 = 10;
+
+/// Port the backend sidecar was started on, `None` until it has been negotiated.
+#[derive(Default)]
+pub struct PortState(pub Option<u16>);
+
+/// The port the backend is currently listening on, so the frontend never has to
+/// hold on to the one from `backend-ready` across restarts.
+#[tauri::command]
+pub async fn get_port(state: State<'_, Mutex<PortState>>) -> Result<u16, EchoError> {
+    state.lock().await.0.ok_or(EchoError::SidecarNotRunning)
+}
+
+/// `preferred_port` from `settings.toml` as it was at launch.
+pub struct PreferredPort(pub Option<u16>);
+
+/// Port tried before scanning: the configured one, else the one the backend
+/// was healthy on during a previous launch.
+pub struct PortPreference {
+    pub preferred: Option<u16>,
+}
+
+impl PortPreference {
+    pub fn load(app: &AppHandle) -> Self {
+        if let Some(port) = app.try_state::<PreferredPort>().and_then(|port| port.0) {
+            return Self {
+                preferred: Some(port),
+            };
+        }
+        let preferred = app
+            .store(STORE_FILE)
+            .ok()
+            .and_then(|store| store.get(PORT_KEY))
+            .and_then(|value| value.as_u64())
+            .and_then(|port| u16::try_from(port).ok());
+        Self { preferred }
+    }
+
+    pub fn save(app: &AppHandle, port: u16) {
+        match app.store(STORE_FILE) {
+            Ok(store) => {
+                store.set(PORT_KEY, port);
+                if let Err(e) = store.save() {
+                    log::warn!("Failed to persist backend port: {}", e);
+                }
+            }
+            Err(e) => log::warn!("Failed to open store: {}", e),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum PortError {
+    /// Every port in `start..=end` was taken or excluded.
+    Exhausted { start: u16, end: u16 },
+    /// Asked to scan zero ports.
+    NoAttempts { start: u16 },
+}
+
+impl fmt::Display for PortError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PortError::Exhausted { start, end } => write!(
+                f,
+                "Could not find a free port between {} and {}; please close conflicting applications.",
+                start, end
+            ),
+            PortError::NoAttempts { start } => {
+                write!(f, "No ports to scan from {}", start)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PortError {}
+
+#[derive(Clone, Serialize)]
+struct PortConflict {
+    message: String,
+    start: u16,
+    end: u16,
+}
+
+/// Pick the port for a new backend, preferring the one that worked last time.
+///
+/// Emits `port-conflict` when no port is free.
+pub fn choose_port(app: &AppHandle) -> Result<u16, PortError> {
+    let result = find_available_port(
+        PORT_RANGE_START,
+        PORT_SCAN_ATTEMPTS,
+        &PortPreference::load(app),
+    );
+    match &result {
+        Ok(port) => log::info!("Using port {} for backend sidecar", port),
+        Err(e) => {
+            log::error!("{}", e);
+            if let PortError::Exhausted { start, end } = *e {
+                let _ = app.emit(
+                    "port-conflict",
+                    PortConflict {
+                        message: e.to_string(),
+                        start,
+                        end,
+                    },
+                );
+            }
+        }
+    }
+    result
+}
+
+pub fn find_available_port(
+    start: u16,
+    attempts: u16,
+    preference: &PortPreference,
+) -> Result<u16, PortError> {
+    let end = last_port(start, attempts).ok_or(PortError::NoAttempts { start })?;
+    let excluded = excluded_ports();
+    if let Some(port) = preference.preferred {
+        if !excluded.contains(&port) && port_is_free(port) {
+            return Ok(port);
+        }
+        log::info!(
+            "Preferred port {} is unavailable, scanning for another",
+            port
+        );
+    }
+    find_available_port_excluding(start, attempts, &excluded)
+        .ok_or(PortError::Exhausted { start, end })
+}
+
+pub fn find_available_port_excluding(start: u16, attempts: u16, excluded: &[u16]) -> Option<u16> {
+    (start..=last_port(start, attempts)?)
+        .filter(|port| !excluded.contains(port))
+        .find(|&port| port_is_free(port))
+}
+
+/// The last of `attempts` ports from `start`, stopping at 65535.
+fn last_port(start: u16, attempts: u16) -> Option<u16> {
+    Some(start.saturating_add(attempts.checked_sub(1)?))
+}
+
+/// First free port in the usual range, ignoring any saved preference.
+pub fn first_free_port() -> Result<u16, PortError> {
+    find_available_port(
+        PORT_RANGE_START,
+        PORT_SCAN_ATTEMPTS,
+        &PortPreference { preferred: None },
+    )
+}
+
+/// Pick a port for an additional backend instance, skipping the ones already
+/// handed out even if their process hasn't bound them yet.
+pub fn choose_extra_port(in_use: &[u16]) -> Result<u16, PortError> {
+    let mut excluded = excluded_ports();
+    excluded.extend_from_slice(in_use);
+    find_available_port_excluding(PORT_RANGE_START, PORT_SCAN_ATTEMPTS, &excluded).ok_or(
+        PortError::Exhausted {
+            start: PORT_RANGE_START,
+            end: PORT_RANGE_START + PORT_SCAN_ATTEMPTS - 1,
+        },
+    )
+}
+
+fn port_is_free(port: u16) -> bool {
+    std::net::TcpListener::bind(("127.0.0.1", port)).is_ok()
+}
+
+/// Ports listed in `ECHO_EXCLUDED_PORTS` (e.g. `8001,8002`), for machines where
+/// a local bind succeeds but the port is reserved for something else.
+fn excluded_ports() -> Vec<u16> {
+    std::env::var("ECHO_EXCLUDED_PORTS")
+        .map(|value| parse_ports(&value))
+        .unwrap_or_default()
+}
+
+/// Invalid entries are skipped.
+fn parse_ports(value: &str) -> Vec<u16> {
+    value
+        .split(',')
+        .filter_map(|port| port.trim().parse().ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    fn unused_port() -> u16 {
+        TcpListener::bind(("127.0.0.1", 0))
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+    }
+
+    #[test]
+    fn excluded_ports_are_parsed_leniently() {
+        assert_eq!(parse_ports("8001,8002"), vec![8001, 8002]);
+        assert_eq!(parse_ports(" 8001 , x, 70000,,8003"), vec![8001, 8003]);
+        assert!(parse_ports("").is_empty());
+    }
+
+    #[test]
+    fn busy_ports_are_skipped() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let busy = listener.local_addr().unwrap().port();
+        assert_eq!(find_available_port_excluding(busy, 1, &[]), None);
+    }
+
+    #[test]
+    fn excluded_ports_are_skipped_even_when_free() {
+        let free = unused_port();
+        assert_eq!(find_available_port_excluding(free, 1, &[]), Some(free));
+        assert_eq!(find_available_port_excluding(free, 1, &[free]), None);
+    }
+
+    #[test]
+    fn scans_stop_at_the_last_port() {
+        assert_eq!(last_port(8000, 10), Some(8009));
+        assert_eq!(last_port(65530, 10), Some(u16::MAX));
+        assert_eq!(last_port(u16::MAX, 1), Some(u16::MAX));
+        assert_eq!(last_port(8000, 0), None);
+        assert_eq!(find_available_port_excluding(8000, 0, &[]), None);
+    }
+
+    #[test]
+    fn zero_attempts_are_rejected() {
+        let preference = PortPreference {
+            preferred: Some(unused_port()),
+        };
+        assert!(matches!(
+            find_available_port(8000, 0, &preference),
+            Err(PortError::NoAttempts { start: 8000 })
+        ));
+    }
+
+    #[test]
+    fn preferred_port_is_tried_first() {
+        let free = unused_port();
+        let preference = PortPreference {
+            preferred: Some(free),
+        };
+        assert_eq!(find_available_port(1, 1, &preference).ok(), Some(free));
+    }
+}
@@ -0,0 +1,134 @@
+//! Battery monitoring, so the frontend can ease off background work on low
+//! battery, and the backend's power-save mode.
+
+use std::time::Duration;
+
+use battery::units::ratio::percent;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::Mutex;
+
+use crate::backend_proxy::BackendClient;
+use crate::error::EchoError;
+use crate::port::PortState;
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const LOW_PERCENT: f32 = 20.0;
+const CRITICAL_PERCENT: f32 = 5.0;
+
+#[derive(Clone, Serialize)]
+pub struct BatteryStatus {
+    pub level_percent: f32,
+    /// Plugged in, whether or not the battery is already full.
+    pub charging: bool,
+}
+
+/// The first battery's status, or `None` on machines without one.
+fn read_battery() -> Option<BatteryStatus> {
+    let manager = battery::Manager::new().ok()?;
+    let battery = manager.batteries().ok()?.flatten().next()?;
+    Some(BatteryStatus {
+        level_percent: battery.state_of_charge().get::<percent>(),
+        charging: matches!(
+            battery.state(),
+            battery::State::Charging | battery::State::Full
+        ),
+    })
+}
+
+#[tauri::command]
+pub fn get_battery_status() -> Option<BatteryStatus> {
+    read_battery()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+enum Level {
+    Normal,
+    Low,
+    Critical,
+}
+
+impl Level {
+    fn of(status: &BatteryStatus) -> Self {
+        if status.charging || status.level_percent > LOW_PERCENT {
+            Level::Normal
+        } else if status.level_percent > CRITICAL_PERCENT {
+            Level::Low
+        } else {
+            Level::Critical
+        }
+    }
+}
+
+/// Emit `battery-low` and `battery-critical`, with the `BatteryStatus`, when
+/// the battery drains past 20% and 5%. Does nothing without a battery.
+pub fn start_monitor(handle: AppHandle) {
+    if read_battery().is_none() {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        let mut last = Level::Normal;
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            let Some(status) = read_battery() else {
+                continue;
+            };
+            let level = Level::of(&status);
+            if level > last {
+                let event = match level {
+                    Level::Critical => "battery-critical",
+                    _ => "battery-low",
+                };
+                log::info!("Battery at {:.0}%", status.level_percent);
+                let _ = handle.emit(event, status);
+            }
+            last = level;
+        }
+    });
+}
+
+/// Ask the backend to poll and flush less often.
+#[tauri::command]
+pub async fn set_power_save_mode(
+    enabled: bool,
+    handle: AppHandle,
+    client: State<'_, BackendClient>,
+) -> Result<(), EchoError> {
+    let port = handle.state::<Mutex<PortState>>().lock().await.0;
+    client.current(port)?.set_power_save(enabled).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(level_percent: f32, charging: bool) -> Level {
+        Level::of(&BatteryStatus {
+            level_percent,
+            charging,
+        })
+    }
+
+    #[test]
+    fn level_follows_the_thresholds() {
+        assert_eq!(level(80.0, false), Level::Normal);
+        assert_eq!(level(20.5, false), Level::Normal);
+        assert_eq!(level(20.0, false), Level::Low);
+        assert_eq!(level(5.5, false), Level::Low);
+        assert_eq!(level(5.0, false), Level::Critical);
+        assert_eq!(level(0.0, false), Level::Critical);
+    }
+
+    #[test]
+    fn charging_is_always_normal() {
+        assert_eq!(level(3.0, true), Level::Normal);
+        assert_eq!(level(15.0, true), Level::Normal);
+    }
+
+    #[test]
+    fn levels_are_ordered_by_severity() {
+        assert!(Level::Normal < Level::Low);
+        assert!(Level::Low < Level::Critical);
+    }
+}
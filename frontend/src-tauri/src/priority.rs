@@ -0,0 +1,84 @@
+//! Scheduling priority of the backend process, so it can be kept from
+//! slowing down foreground work on constrained machines.
+
+use serde::Deserialize;
+use tauri::State;
+use tokio::sync::Mutex;
+
+use crate::error::EchoError;
+use crate::sidecar::SidecarState;
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProcessPriority {
+    Low,
+    Normal,
+    High,
+}
+
+/// Raising priority needs root on Unix, so `High` fails for normal users.
+#[cfg(unix)]
+fn set_priority(pid: u32, priority: ProcessPriority) -> Result<(), EchoError> {
+    let nice = match priority {
+        ProcessPriority::Low => 10,
+        ProcessPriority::Normal => 0,
+        ProcessPriority::High => -5,
+    };
+    // SAFETY: plain syscall on a pid; failure is reported through errno
+    if unsafe { libc::setpriority(libc::PRIO_PROCESS, pid, nice) } != 0 {
+        let e = std::io::Error::last_os_error();
+        return Err(match e.kind() {
+            std::io::ErrorKind::PermissionDenied => EchoError::Unauthorized,
+            _ => format!("failed to set backend priority: {}", e).into(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn set_priority(pid: u32, priority: ProcessPriority) -> Result<(), EchoError> {
+    use windows_sys::Win32::Foundation::CloseHandle;
+    use windows_sys::Win32::System::Threading::{
+        OpenProcess, SetPriorityClass, ABOVE_NORMAL_PRIORITY_CLASS, BELOW_NORMAL_PRIORITY_CLASS,
+        NORMAL_PRIORITY_CLASS, PROCESS_SET_INFORMATION,
+    };
+
+    let class = match priority {
+        ProcessPriority::Low => BELOW_NORMAL_PRIORITY_CLASS,
+        ProcessPriority::Normal => NORMAL_PRIORITY_CLASS,
+        ProcessPriority::High => ABOVE_NORMAL_PRIORITY_CLASS,
+    };
+    // SAFETY: the handle is checked before use and closed afterwards
+    unsafe {
+        let process = OpenProcess(PROCESS_SET_INFORMATION, 0, pid);
+        if process.is_null() {
+            return Err(format!(
+                "failed to open backend process: {}",
+                std::io::Error::last_os_error()
+            )
+            .into());
+        }
+        let set = SetPriorityClass(process, class);
+        let e = std::io::Error::last_os_error();
+        CloseHandle(process);
+        if set == 0 {
+            return Err(format!("failed to set backend priority: {}", e).into());
+        }
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_backend_priority(
+    priority: ProcessPriority,
+    state: State<'_, Mutex<SidecarState>>,
+) -> Result<(), EchoError> {
+    let pid = state
+        .lock()
+        .await
+        .child
+        .as_ref()
+        .map(|child| child.pid())
+        .ok_or(EchoError::SidecarNotRunning)?;
+    set_priority(pid, priority)
+}
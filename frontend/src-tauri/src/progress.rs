@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+
+use crate::backend_proxy::BackendClient;
+use crate::error::EchoError;
+use crate::port::PortState;
+
+/// Cancellation tokens of the open progress streams, by operation id.
+#[derive(Default)]
+pub struct ProgressSubscriptions(HashMap<String, CancellationToken>);
+
+impl ProgressSubscriptions {
+    pub fn operation_ids(&self) -> Vec<String> {
+        self.0.keys().cloned().collect()
+    }
+}
+
+/// Ids end up in the backend URL and in an event name, which Tauri only
+/// allows to contain alphanumerics, `-`, `/`, `:` and `_`.
+fn is_valid_operation_id(operation_id: &str) -> bool {
+    !operation_id.is_empty()
+        && operation_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Forward the backend's `/progress/{operation_id}` event stream as
+/// `progress-{operation_id}` events until it ends or is unsubscribed.
+/// `operation_id` may only contain ASCII letters, digits, `-` and `_`.
+#[tauri::command]
+pub async fn subscribe_progress(
+    operation_id: String,
+    handle: AppHandle,
+    state: State<'_, BackendClient>,
+) -> Result<(), EchoError> {
+    if !is_valid_operation_id(&operation_id) {
+        return Err(EchoError::invalid(format!(
+            "invalid operation id {:?}",
+            operation_id
+        )));
+    }
+    let port = handle.state::<Mutex<PortState>>().lock().await.0;
+    let resp = state
+        .current(port)?
+        .progress(&operation_id)
+        .await
+        .map_err(EchoError::backend)?;
+
+    let token = CancellationToken::new();
+    let subscriptions = handle.state::<Mutex<ProgressSubscriptions>>();
+    if let Some(old) = subscriptions
+        .lock()
+        .await
+        .0
+        .insert(operation_id.clone(), token.clone())
+    {
+        old.cancel();
+    }
+
+    tauri::async_runtime::spawn(async move {
+        tokio::select! {
+            _ = token.cancelled() => {}
+            result = forward(&handle, &operation_id, resp) => {
+                if let Err(e) = result {
+                    log::warn!("Progress stream for {} failed: {}", operation_id, e);
+                }
+                // Not cancelled, so the entry is still ours to remove
+                handle
+                    .state::<Mutex<ProgressSubscriptions>>()
+                    .lock()
+                    .await
+                    .0
+                    .remove(&operation_id);
+            }
+        }
+    });
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn unsubscribe_progress(operation_id: String, handle: AppHandle) {
+    let subscriptions = handle.state::<Mutex<ProgressSubscriptions>>();
+    let token = subscriptions.lock().await.0.remove(&operation_id);
+    if let Some(token) = token {
+        token.cancel();
+    }
+}
+
+/// Emit one event per SSE message. A message's `data:` lines are joined with
+/// newlines and sent as JSON if they parse, otherwise as a string.
+async fn forward(
+    handle: &AppHandle,
+    operation_id: &str,
+    mut resp: reqwest::Response,
+) -> reqwest::Result<()> {
+    let event = format!("progress-{}", operation_id);
+    let mut buf = Vec::new();
+    let mut data: Vec<String> = Vec::new();
+
+    while let Some(chunk) = resp.chunk().await? {
+        buf.extend_from_slice(&chunk);
+        while let Some(pos) = buf.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = buf.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\r', '\n']);
+
+            if line.is_empty() {
+                if !data.is_empty() {
+                    let text = data.join("\n");
+                    let payload =
+                        serde_json::from_str(&text).unwrap_or(serde_json::Value::String(text));
+                    let _ = handle.emit(&event, payload);
+                    data.clear();
+                }
+            } else if let Some(value) = line.strip_prefix("data:") {
+                data.push(value.strip_prefix(' ').unwrap_or(value).to_string());
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn operation_ids_are_restricted() {
+        for id in ["convert-42", "exam_upload", "A1"] {
+            assert!(is_valid_operation_id(id), "{id}");
+        }
+        for id in ["", "../shutdown", "42?x=1", "a.b", "a b", "a/b", "é"] {
+            assert!(!is_valid_operation_id(id), "{id}");
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, State};
+use tauri_plugin_store::StoreExt;
+
+use crate::error::EchoError;
+use crate::logs::now_ms;
+use crate::STORE_FILE;
+
+const RECENT_FILES_KEY: &str = "recent_files";
+const MAX_RECENT_FILES: usize = 20;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RecentFile {
+    pub path: String,
+    /// Milliseconds since the Unix epoch.
+    pub opened_at: u64,
+    pub label: Option<String>,
+}
+
+/// Most recently opened files, newest first, mirrored to the store.
+pub struct RecentFiles(Mutex<VecDeque<RecentFile>>);
+
+impl RecentFiles {
+    pub fn load(app: &AppHandle) -> Self {
+        let files = app
+            .store(STORE_FILE)
+            .ok()
+            .and_then(|store| store.get(RECENT_FILES_KEY))
+            .and_then(|value| serde_json::from_value(value).ok())
+            .unwrap_or_default();
+        Self(Mutex::new(files))
+    }
+
+    /// Read the list from the store again, after it was cleared or replaced.
+    pub fn reload(&self, app: &AppHandle) {
+        *self.0.lock().unwrap() = Self::load(app).0.into_inner().unwrap();
+    }
+}
+
+/// Save the list and emit `recent-files-changed` with it.
+fn persist(app: &AppHandle, files: &VecDeque<RecentFile>) -> Result<(), EchoError> {
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    store.set(RECENT_FILES_KEY, serde_json::to_value(files)?);
+    store
+        .save()
+        .map_err(|e| format!("failed to save recent files: {}", e))?;
+    let _ = app.emit("recent-files-changed", files);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_recent_files(recent: State<'_, RecentFiles>) -> Vec<RecentFile> {
+    recent.0.lock().unwrap().iter().cloned().collect()
+}
+
+/// Put `path` at the top of the list, moving it there if it is already listed.
+#[tauri::command]
+pub fn add_recent_file(
+    path: String,
+    label: Option<String>,
+    app: AppHandle,
+    recent: State<'_, RecentFiles>,
+) -> Result<(), EchoError> {
+    let mut files = recent.0.lock().unwrap();
+    files.retain(|file| file.path != path);
+    files.push_front(RecentFile {
+        path,
+        opened_at: now_ms(),
+        label,
+    });
+    files.truncate(MAX_RECENT_FILES);
+    persist(&app, &files)
+}
+
+#[tauri::command]
+pub fn clear_recent_files(app: AppHandle, recent: State<'_, RecentFiles>) -> Result<(), EchoError> {
+    let mut files = recent.0.lock().unwrap();
+    files.clear();
+    persist(&app, &files)
+}
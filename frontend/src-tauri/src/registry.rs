@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+use std::time::Instant;
+
+use serde::Serialize;
+use tauri::async_runtime::Receiver;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_shell::process::{CommandChild, CommandEvent};
+use tokio::sync::Mutex;
+
+use crate::error::EchoError;
+use crate::logs::{self, LogLevel};
+use crate::port::{self, PortState};
+use crate::sidecar::{self, SidecarConfig, SidecarEnv, SidecarState};
+
+/// Name of the backend started at launch. It lives in `SidecarState` rather
+/// than the registry because it is supervised: health-polled, watched and
+/// restarted on crashes, and used by every other command.
+pub const DEFAULT_SIDECAR: &str = "default";
+
+pub struct SidecarEntry {
+    pub child: CommandChild,
+    pub port: u16,
+    pub start_time: Instant,
+}
+
+/// Additional named backend instances, e.g. one per workspace. These are not
+/// restarted if they exit.
+#[derive(Default)]
+pub struct SidecarRegistry(pub HashMap<String, SidecarEntry>);
+
+/// Start a backend called `name` on a free port and return the port.
+#[tauri::command]
+pub async fn spawn_sidecar(name: String, handle: AppHandle) -> Result<u16, EchoError> {
+    if name == DEFAULT_SIDECAR {
+        return spawn_default(&handle).await;
+    }
+
+    let registry = handle.state::<Mutex<SidecarRegistry>>();
+    let mut registry = registry.lock().await;
+    if registry.0.contains_key(&name) {
+        return Err(EchoError::invalid(format!(
+            "sidecar {} is already running",
+            name
+        )));
+    }
+
+    let mut in_use: Vec<u16> = registry.0.values().map(|entry| entry.port).collect();
+    in_use.extend(handle.state::<Mutex<PortState>>().lock().await.0);
+    let port = port::choose_extra_port(&in_use)?;
+
+    let config = handle.state::<Mutex<SidecarConfig>>().lock().await.clone();
+    let env = handle.state::<Mutex<SidecarEnv>>().lock().await.0.clone();
+    let (rx, child) = sidecar::spawn_process(&config, &handle, port, env)
+        .map_err(|e| format!("failed to spawn sidecar {}: {}", name, e))?;
+    log::info!("Started sidecar {} on port {}", name, port);
+
+    let pid = child.pid();
+    registry.0.insert(
+        name.clone(),
+        SidecarEntry {
+            child,
+            port,
+            start_time: Instant::now(),
+        },
+    );
+    tauri::async_runtime::spawn(monitor(handle.clone(), name, pid, rx));
+    Ok(port)
+}
+
+/// Start the supervised default backend on the preferred or first free port.
+pub async fn spawn_default(handle: &AppHandle) -> Result<u16, EchoError> {
+    if handle
+        .state::<Mutex<SidecarState>>()
+        .lock()
+        .await
+        .child
+        .is_some()
+    {
+        return Err(EchoError::invalid(format!(
+            "sidecar {} is already running",
+            DEFAULT_SIDECAR
+        )));
+    }
+    let port = port::choose_port(handle)?;
+    handle.state::<Mutex<PortState>>().lock().await.0 = Some(port);
+    sidecar::spawn(handle, port)
+        .await
+        .map_err(|e| format!("failed to spawn sidecar: {}", e))?;
+    Ok(port)
+}
+
+#[tauri::command]
+pub async fn kill_sidecar(name: String, handle: AppHandle) -> Result<(), EchoError> {
+    if name == DEFAULT_SIDECAR {
+        sidecar::shutdown_default(&handle).await;
+        return Ok(());
+    }
+
+    let entry = handle
+        .state::<Mutex<SidecarRegistry>>()
+        .lock()
+        .await
+        .0
+        .remove(&name)
+        .ok_or_else(|| EchoError::invalid(format!("no sidecar named {}", name)))?;
+    log::info!("Stopping sidecar {}", name);
+    entry
+        .child
+        .kill()
+        .map_err(|e| format!("failed to kill sidecar {}: {}", name, e).into())
+}
+
+#[derive(Serialize)]
+pub struct SidecarInfo {
+    pub name: String,
+    pub port: u16,
+    pub pid: u32,
+    pub uptime_secs: u64,
+}
+
+/// Instances started with `spawn_sidecar`, not including the default one.
+#[tauri::command]
+pub async fn list_sidecars(handle: AppHandle) -> Vec<SidecarInfo> {
+    let registry = handle.state::<Mutex<SidecarRegistry>>();
+    let registry = registry.lock().await;
+    registry
+        .0
+        .iter()
+        .map(|(name, entry)| SidecarInfo {
+            name: name.clone(),
+            port: entry.port,
+            pid: entry.child.pid(),
+            uptime_secs: entry.start_time.elapsed().as_secs(),
+        })
+        .collect()
+}
+
+/// Kill every registered instance, e.g. when the app is closing.
+pub async fn kill_all(handle: &AppHandle) {
+    let entries = std::mem::take(&mut handle.state::<Mutex<SidecarRegistry>>().lock().await.0);
+    for (name, entry) in entries {
+        log::info!("Stopping sidecar {}", name);
+        let _ = entry.child.kill();
+    }
+}
+
+/// `pid` tells this process's entry apart from a later instance with the same
+/// name, started after it was killed but before its `Terminated` arrived.
+async fn monitor(handle: AppHandle, name: String, pid: u32, mut rx: Receiver<CommandEvent>) {
+    while let Some(event) = rx.recv().await {
+        match event {
+            CommandEvent::Stdout(line) => {
+                let line = format!("[{}] {}", name, String::from_utf8_lossy(&line));
+                log::info!("[sidecar] {}", line);
+                logs::record(&handle, LogLevel::Info, &line);
+            }
+            CommandEvent::Stderr(line) => {
+                let line = format!("[{}] {}", name, String::from_utf8_lossy(&line));
+                log::warn!("[sidecar] {}", line);
+                logs::record(&handle, LogLevel::Warn, &line);
+            }
+            CommandEvent::Terminated(status) => {
+                log::info!("[sidecar] {} terminated with status: {:?}", name, status);
+                let registry = handle.state::<Mutex<SidecarRegistry>>();
+                let mut registry = registry.lock().await;
+                if registry
+                    .0
+                    .get(&name)
+                    .is_some_and(|entry| entry.child.pid() == pid)
+                {
+                    registry.0.remove(&name);
+                }
+                break;
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,184 @@
+//! A snapshot of the app's state for bug reports: configuration, recent
+//! backend output, diagnostics, startup timing and the sidecar's status.
+
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tokio::sync::Mutex;
+
+use crate::app_config::AppConfig;
+use crate::diagnostics::{self, CheckStatus, DiagnosticReport};
+use crate::dialog;
+use crate::error::EchoError;
+use crate::logs::{now_ms, LogBuffer, LogEntry, LogLevel};
+use crate::sidecar::{self, SidecarStatus};
+use crate::startup::{self, StartupTimer, StartupTiming};
+
+const REPORT_LOG_ENTRIES: usize = 100;
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    Json,
+    /// Only the log entries, one per row.
+    Csv,
+    Markdown,
+}
+
+#[derive(Serialize)]
+struct Report {
+    generated_at: u64,
+    version: String,
+    /// With the `env` values masked.
+    config: AppConfig,
+    logs: Vec<LogEntry>,
+    diagnostics: DiagnosticReport,
+    startup_timing: Option<StartupTiming>,
+    sidecar: SidecarStatus,
+}
+
+impl Report {
+    async fn collect(app: &AppHandle) -> Self {
+        Self {
+            generated_at: now_ms(),
+            version: app.package_info().version.to_string(),
+            config: app.state::<Mutex<AppConfig>>().lock().await.redacted(),
+            logs: app.state::<LogBuffer>().recent(REPORT_LOG_ENTRIES),
+            diagnostics: diagnostics::run_diagnostic(app.clone()).await,
+            startup_timing: startup::get_startup_timing(app.state::<StartupTimer>()),
+            sidecar: sidecar::get_sidecar_status(app.clone()).await,
+        }
+    }
+
+    fn to_csv(&self) -> String {
+        let mut out = String::from("timestamp,level,message\n");
+        for entry in &self.logs {
+            let _ = writeln!(
+                out,
+                "{},{},\"{}\"",
+                entry.timestamp,
+                level(entry.level),
+                entry.message.replace('"', "\"\"")
+            );
+        }
+        out
+    }
+
+    fn to_markdown(&self) -> Result<String, EchoError> {
+        let mut out = String::new();
+        let _ = writeln!(out, "# Echo {} report\n", self.version);
+        let _ = writeln!(
+            out,
+            "Generated at {} (ms since the epoch)\n",
+            self.generated_at
+        );
+
+        let sidecar = &self.sidecar;
+        let _ = writeln!(out, "## Backend\n");
+        let _ = writeln!(out, "- Running: {}", sidecar.running);
+        let _ = writeln!(out, "- PID: {}", optional(sidecar.pid));
+        let _ = writeln!(out, "- Port: {}", optional(sidecar.port));
+        let _ = writeln!(out, "- Uptime: {} s", optional(sidecar.uptime_secs));
+        let _ = writeln!(out, "- Restarts: {}", sidecar.restart_count);
+        let _ = writeln!(
+            out,
+            "- Last error: {}\n",
+            sidecar.last_error.as_deref().unwrap_or("none")
+        );
+
+        let _ = writeln!(out, "## Startup\n");
+        match &self.startup_timing {
+            Some(timing) => {
+                let _ = writeln!(out, "- Total: {} ms", timing.total_ms);
+                let _ = writeln!(out, "- Sidecar spawned: {} ms", timing.sidecar_spawn_ms);
+                let _ = writeln!(out, "- Backend healthy: {} ms", timing.health_poll_ms);
+                let _ = writeln!(out, "- Window shown: {} ms\n", timing.window_shown_ms);
+            }
+            None => out.push_str("Startup has not finished.\n\n"),
+        }
+
+        let _ = writeln!(out, "## Diagnostics\n");
+        out.push_str("| Check | Status | Detail |\n| --- | --- | --- |\n");
+        for check in &self.diagnostics.checks {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} |",
+                check.name,
+                status(&check.status),
+                check.detail.as_deref().unwrap_or("").replace('|', "\\|")
+            );
+        }
+
+        let config = toml::to_string_pretty(&self.config)
+            .map_err(|e| format!("failed to serialize config: {}", e))?;
+        let _ = writeln!(out, "\n## Configuration\n\n```toml\n{}```\n", config);
+
+        let _ = writeln!(out, "## Recent backend output\n\n```");
+        for entry in &self.logs {
+            let _ = writeln!(
+                out,
+                "{} {} {}",
+                entry.timestamp,
+                level(entry.level),
+                entry.message
+            );
+        }
+        out.push_str("```\n");
+        Ok(out)
+    }
+}
+
+fn level(level: LogLevel) -> &'static str {
+    match level {
+        LogLevel::Info => "info",
+        LogLevel::Warn => "warn",
+    }
+}
+
+fn status(status: &CheckStatus) -> &'static str {
+    match status {
+        CheckStatus::Pass => "pass",
+        CheckStatus::Warn => "warn",
+        CheckStatus::Fail => "fail",
+    }
+}
+
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map_or_else(|| "none".into(), |value| value.to_string())
+}
+
+/// The report as a file's contents, e.g. for a path picked with `save_file`.
+#[tauri::command]
+pub async fn generate_report(format: ReportFormat, app: AppHandle) -> Result<Vec<u8>, EchoError> {
+    let report = Report::collect(&app).await;
+    Ok(match format {
+        ReportFormat::Json => serde_json::to_vec_pretty(&report)?,
+        ReportFormat::Csv => report.to_csv().into_bytes(),
+        ReportFormat::Markdown => report.to_markdown()?.into_bytes(),
+    })
+}
+
+/// Write the report to `path`, which must have been chosen with `save_file`
+/// or inside a folder from `pick_directory`.
+#[tauri::command]
+pub async fn generate_report_to_file(
+    format: ReportFormat,
+    path: String,
+    app: AppHandle,
+) -> Result<(), EchoError> {
+    let path = PathBuf::from(path);
+    if !dialog::is_picked(&app, &path) {
+        log::warn!("Refusing to write report to {}", path.display());
+        return Err(EchoError::Unauthorized);
+    }
+    let bytes = generate_report(format, app).await?;
+    tokio::fs::write(&path, bytes).await.map_err(|e| {
+        EchoError::io(format!(
+            "Failed to write report to {}: {}",
+            path.display(),
+            e
+        ))
+    })
+}
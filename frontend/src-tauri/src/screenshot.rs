@@ -0,0 +1,63 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use tauri::{AppHandle, Manager, WebviewWindow};
+
+use crate::error::EchoError;
+
+/// Grab the window labelled `window_label` as a Base64-encoded PNG, e.g. to
+/// attach to a bug report.
+#[tauri::command]
+pub fn capture_screenshot(app: AppHandle, window_label: String) -> Result<String, EchoError> {
+    let window = app
+        .get_webview_window(&window_label)
+        .ok_or_else(|| EchoError::invalid(format!("no window labelled {:?}", window_label)))?;
+    let png = capture(&window)?;
+    Ok(STANDARD.encode(png))
+}
+
+#[cfg(any(windows, target_os = "macos"))]
+fn capture(window: &WebviewWindow) -> Result<Vec<u8>, String> {
+    let title = window.title().map_err(|e| e.to_string())?;
+    let pid = std::process::id();
+    let target = xcap::Window::all()
+        .map_err(|e| format!("failed to list windows: {}", e))?
+        .into_iter()
+        .find(|w| w.pid().ok() == Some(pid) && w.title().ok().as_deref() == Some(title.as_str()))
+        .ok_or_else(|| format!("window {:?} is not on screen", window.label()))?;
+
+    let image = target
+        .capture_image()
+        .map_err(|e| format!("failed to capture window: {}", e))?;
+    encode_png(&image)
+}
+
+#[cfg(any(windows, target_os = "macos"))]
+fn encode_png(image: &xcap::image::RgbaImage) -> Result<Vec<u8>, String> {
+    use std::io::Cursor;
+    use xcap::image::ImageFormat;
+
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .map_err(|e| format!("failed to encode screenshot: {}", e))?;
+    Ok(png)
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+fn capture(_window: &WebviewWindow) -> Result<Vec<u8>, String> {
+    Err("window screenshots are not supported on this platform".into())
+}
+
+#[cfg(all(test, any(windows, target_os = "macos")))]
+mod tests {
+    use super::*;
+
+    const PNG_MAGIC: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+    #[test]
+    fn screenshots_are_png() {
+        let image = xcap::image::RgbaImage::from_pixel(4, 3, xcap::image::Rgba([255, 0, 0, 255]));
+        let png = encode_png(&image).unwrap();
+        assert_eq!(png[..PNG_MAGIC.len()], PNG_MAGIC);
+    }
+}
@@ -0,0 +1,188 @@
+//! Settings export and import as a ZIP archive, for moving to another machine.
+//!
+//! The archive holds a `manifest.json` with the app version it came from,
+//! `settings.toml` (`AppConfig`) and `store.json` (the user settings from the
+//! store, see `STORE_SETTINGS`). Window geometry is left out since it depends
+//! on the monitors of the machine it was saved on.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_store::StoreExt;
+use tokio::sync::Mutex;
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+use crate::app_config::{self, AppConfig, ConfigChangeResult};
+use crate::backend_proxy::BackendClient;
+use crate::dialog;
+use crate::error::EchoError;
+use crate::logs::now_ms;
+use crate::sidecar::SidecarConfig;
+use crate::theme::Theme;
+use crate::STORE_FILE;
+
+const MANIFEST: &str = "manifest.json";
+const SETTINGS: &str = "settings.toml";
+const STORE: &str = "store.json";
+
+/// Store keys that are user settings. The rest of the store belongs to this
+/// install (port, migrated version, history, scheduled tasks and the like) and
+/// is neither exported nor overwritten by an import.
+const STORE_SETTINGS: &[&str] = &[
+    "locale",
+    "theme",
+    "always_on_top",
+    "minimize_to_tray",
+    "remote_backend_url",
+    "proxy_config",
+];
+
+#[derive(Serialize, Deserialize)]
+struct Manifest {
+    /// App version that wrote the archive.
+    version: String,
+    exported_at: u64,
+}
+
+fn zip_error(e: zip::result::ZipError) -> EchoError {
+    EchoError::io(format!("invalid settings archive: {}", e))
+}
+
+/// Write the current settings to a ZIP archive at `path`, which must have been
+/// chosen with `save_file` or inside a folder from `pick_directory`.
+#[tauri::command]
+pub async fn export_settings(path: String, app: AppHandle) -> Result<(), EchoError> {
+    if !dialog::is_picked(&app, Path::new(&path)) {
+        log::warn!("Refusing to export settings to {}", path);
+        return Err(EchoError::Unauthorized);
+    }
+
+    let manifest = Manifest {
+        version: app.package_info().version.to_string(),
+        exported_at: now_ms(),
+    };
+    let config = app.state::<Mutex<AppConfig>>().lock().await.clone();
+    let settings = toml::to_string_pretty(&config)?;
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    let store: HashMap<&str, serde_json::Value> = STORE_SETTINGS
+        .iter()
+        .filter_map(|&key| Some((key, store.get(key)?)))
+        .collect();
+
+    let mut zip = ZipWriter::new(File::create(&path)?);
+    let files = [
+        (MANIFEST, serde_json::to_string_pretty(&manifest)?),
+        (SETTINGS, settings),
+        (STORE, serde_json::to_string_pretty(&store)?),
+    ];
+    for (name, contents) in files {
+        zip.start_file(name, SimpleFileOptions::default())
+            .map_err(zip_error)?;
+        zip.write_all(contents.as_bytes())?;
+    }
+    zip.finish().map_err(zip_error)?;
+    log::info!("Exported settings to {}", path);
+    Ok(())
+}
+
+/// Apply the settings from an archive made by `export_settings`, including the
+/// theme, proxy and remote backend in use. Archives from a newer app version,
+/// or with any entry besides the expected files, are rejected before anything
+/// is changed.
+#[tauri::command]
+pub async fn import_settings(
+    path: String,
+    app: AppHandle,
+) -> Result<ConfigChangeResult, EchoError> {
+    let mut zip = ZipArchive::new(File::open(&path)?).map_err(zip_error)?;
+    for i in 0..zip.len() {
+        let entry = zip.by_index(i).map_err(zip_error)?;
+        let name = entry.enclosed_name().ok_or_else(|| {
+            EchoError::invalid(format!(
+                "archive entry {:?} escapes the archive",
+                entry.name()
+            ))
+        })?;
+        if !matches!(name.to_str(), Some(MANIFEST | SETTINGS | STORE)) {
+            return Err(EchoError::invalid(format!(
+                "unexpected archive entry {:?}",
+                entry.name()
+            )));
+        }
+    }
+
+    let manifest: Manifest = serde_json::from_str(&read_entry(&mut zip, MANIFEST)?)?;
+    let exported = semver::Version::parse(&manifest.version).map_err(|e| {
+        EchoError::invalid(format!("invalid version {:?}: {}", manifest.version, e))
+    })?;
+    let running = &app.package_info().version;
+    if exported > *running {
+        return Err(EchoError::invalid(format!(
+            "settings are from version {}, newer than this app ({})",
+            exported, running
+        )));
+    }
+
+    let config: AppConfig = toml::from_str(&read_entry(&mut zip, SETTINGS)?)?;
+    config.log_level()?;
+    let entries: HashMap<String, serde_json::Value> =
+        serde_json::from_str(&read_entry(&mut zip, STORE)?)?;
+
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    for (key, value) in entries {
+        if STORE_SETTINGS.contains(&key.as_str()) {
+            store.set(key, value);
+        } else {
+            log::warn!("Ignoring {:?} in settings archive", key);
+        }
+    }
+    store.save().map_err(|e| e.to_string())?;
+    log::info!("Imported settings from {} (version {})", path, exported);
+
+    let mut result = app_config::write_config(config, app.clone()).await?;
+    reload(&app, &mut result).await?;
+    Ok(result)
+}
+
+/// Bring what is in memory in line with the imported store.
+async fn reload(app: &AppHandle, result: &mut ConfigChangeResult) -> Result<(), EchoError> {
+    Theme::load(app)
+        .apply(app)
+        .map_err(|e| format!("failed to apply theme: {}", e))?;
+    app.state::<BackendClient>().reload(app)?;
+
+    #[cfg(desktop)]
+    {
+        let on_top = crate::windows::saved_always_on_top(app);
+        if let Some(window) = app.get_webview_window("main") {
+            window
+                .set_always_on_top(on_top)
+                .map_err(|e| format!("failed to set always on top: {}", e))?;
+        }
+        crate::tray::set_always_on_top_checked(app, on_top);
+    }
+
+    // Passed to the backend on its command line
+    let locale = SidecarConfig::load(app).locale;
+    let state = app.state::<Mutex<SidecarConfig>>();
+    let mut config = state.lock().await;
+    if config.locale != locale {
+        config.locale = locale;
+        result.requires_restart.push("locale");
+    }
+    Ok(())
+}
+
+fn read_entry(zip: &mut ZipArchive<File>, name: &str) -> Result<String, EchoError> {
+    let mut entry = zip
+        .by_name(name)
+        .map_err(|_| EchoError::invalid(format!("settings archive is missing {}", name)))?;
+    let mut contents = String::new();
+    entry.read_to_string(&mut contents)?;
+    Ok(contents)
+}
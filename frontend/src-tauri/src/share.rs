@@ -0,0 +1,62 @@
+//! Handing content to other apps. Android and iOS use the system share sheet,
+//! which only takes text; desktop has no share sheet this can open without an
+//! anchor view, so the content is copied to the clipboard instead.
+
+use serde::Deserialize;
+use tauri::AppHandle;
+
+use crate::error::EchoError;
+
+#[derive(Deserialize)]
+pub struct ShareContent {
+    pub text: Option<String>,
+    pub url: Option<String>,
+    /// Paths, shared as text since neither target can attach files.
+    #[serde(default)]
+    pub files: Vec<String>,
+}
+
+impl ShareContent {
+    /// Everything on its own line, text first.
+    fn to_text(&self) -> String {
+        self.text
+            .iter()
+            .chain(&self.url)
+            .chain(&self.files)
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Share `content` through the share sheet, or copy it to the clipboard and
+/// emit `share-fallback-clipboard` where there is none.
+#[tauri::command]
+pub fn share_via_os(content: ShareContent, app: AppHandle) -> Result<(), EchoError> {
+    let text = content.to_text();
+    if text.is_empty() {
+        return Err(EchoError::invalid("nothing to share"));
+    }
+    share(&app, text)
+}
+
+#[cfg(any(target_os = "android", target_os = "ios"))]
+fn share(app: &AppHandle, text: String) -> Result<(), EchoError> {
+    use tauri_plugin_sharesheet::{SharesheetExt, SharesheetOptions};
+
+    app.share_text()
+        .share_text(text, SharesheetOptions::default())
+        .map_err(|e| format!("failed to open the share sheet: {}", e).into())
+}
+
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+fn share(app: &AppHandle, text: String) -> Result<(), EchoError> {
+    use tauri::Emitter;
+    use tauri_plugin_clipboard_manager::ClipboardExt;
+
+    app.clipboard()
+        .write_text(text)
+        .map_err(|e| format!("failed to copy shared content: {}", e))?;
+    let _ = app.emit("share-fallback-clipboard", ());
+    Ok(())
+}
@@ -0,0 +1,131 @@
+//! System-wide keyboard shortcuts that fire even while the app is in the
+//! background. Each shortcut carries an action name the frontend interprets.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+use crate::clipboard;
+use crate::error::EchoError;
+
+/// Triggers `import_from_clipboard` from any app.
+const IMPORT_SHORTCUT: &str = "CmdOrCtrl+Alt+V";
+
+/// Registered shortcuts in their normalized form (e.g. `shift+control+KeyE`), mapped to their action.
+#[derive(Default)]
+pub struct GlobalShortcutRegistry(Mutex<HashMap<String, String>>);
+
+#[derive(Clone, Serialize)]
+struct ShortcutTriggered {
+    action: String,
+}
+
+fn parse(shortcut: &str) -> Result<Shortcut, EchoError> {
+    shortcut
+        .parse()
+        .map_err(|e| EchoError::invalid(format!("invalid shortcut {}: {}", shortcut, e)))
+}
+
+/// Register an accelerator such as `CmdOrCtrl+Shift+E`, replacing any action
+/// it already had. Emits `shortcut-triggered { action }` when pressed.
+#[tauri::command]
+pub fn register_global_shortcut(
+    shortcut: String,
+    action: String,
+    app: AppHandle,
+    registry: State<'_, GlobalShortcutRegistry>,
+) -> Result<(), EchoError> {
+    let parsed = parse(&shortcut)?;
+    let global_shortcut = app.global_shortcut();
+    if global_shortcut.is_registered(parsed) {
+        global_shortcut
+            .unregister(parsed)
+            .map_err(|e| format!("failed to replace shortcut {}: {}", shortcut, e))?;
+    }
+
+    let triggered = action.clone();
+    global_shortcut
+        .on_shortcut(parsed, move |app, _, event| {
+            if event.state == ShortcutState::Pressed {
+                let _ = app.emit(
+                    "shortcut-triggered",
+                    ShortcutTriggered {
+                        action: triggered.clone(),
+                    },
+                );
+            }
+        })
+        .map_err(|e| format!("failed to register shortcut {}: {}", shortcut, e))?;
+
+    registry
+        .0
+        .lock()
+        .unwrap()
+        .insert(parsed.into_string(), action);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn unregister_global_shortcut(
+    shortcut: String,
+    app: AppHandle,
+    registry: State<'_, GlobalShortcutRegistry>,
+) -> Result<(), EchoError> {
+    let parsed = parse(&shortcut)?;
+    app.global_shortcut()
+        .unregister(parsed)
+        .map_err(|e| format!("failed to unregister shortcut {}: {}", shortcut, e))?;
+    registry.0.lock().unwrap().remove(&parsed.into_string());
+    Ok(())
+}
+
+/// `(shortcut, action)` pairs, sorted by shortcut.
+#[tauri::command]
+pub fn get_registered_shortcuts(
+    registry: State<'_, GlobalShortcutRegistry>,
+) -> Vec<(String, String)> {
+    let mut shortcuts: Vec<_> = registry
+        .0
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(shortcut, action)| (shortcut.clone(), action.clone()))
+        .collect();
+    shortcuts.sort();
+    shortcuts
+}
+
+/// Register `IMPORT_SHORTCUT`. Another app may already hold it, in which case
+/// importing is only available from inside the app.
+pub fn register_import_shortcut(app: &AppHandle) {
+    let shortcut = match parse(IMPORT_SHORTCUT) {
+        Ok(shortcut) => shortcut,
+        Err(e) => return log::warn!("{}", e),
+    };
+    let registered = app
+        .global_shortcut()
+        .on_shortcut(shortcut, |app, _, event| {
+            if event.state != ShortcutState::Pressed {
+                return;
+            }
+            let handle = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = clipboard::import_from_clipboard(handle).await {
+                    log::warn!("Clipboard import failed: {}", e);
+                }
+            });
+        });
+    match registered {
+        Ok(()) => {
+            app.state::<GlobalShortcutRegistry>()
+                .0
+                .lock()
+                .unwrap()
+                .insert(shortcut.into_string(), "import_from_clipboard".into());
+        }
+        Err(e) => log::warn!("Failed to register {}: {}", IMPORT_SHORTCUT, e),
+    }
+}
@@ -0,0 +1,1021 @@
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tauri::async_runtime::Receiver;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_shell::process::{CommandChild, CommandEvent};
+use tauri_plugin_shell::ShellExt;
+use tauri_plugin_store::StoreExt;
+use tokio::sync::{Mutex, Notify};
+use tokio_util::sync::CancellationToken;
+
+use crate::backend_proxy::{BackendClient, BackendProxy, Transport};
+use crate::command_queue;
+use crate::error::EchoError;
+use crate::logs::{self, LogLevel};
+use crate::metrics::{ProcessSample, METRICS_CAPACITY};
+use crate::migration;
+use crate::port::{self, PortPreference, PortState};
+use crate::registry;
+use crate::startup;
+use crate::tls;
+use crate::watchdog::{self, WatchdogConfig};
+use crate::windows;
+use crate::STORE_FILE;
+
+/// Consecutive restarts attempted before giving up on the backend.
+const MAX_RESTARTS: u32 = 5;
+
+/// Store key for the locale chosen with `set_locale`.
+const LOCALE_KEY: &str = "locale";
+
+/// Oldest backend whose API this frontend works with.
+const MIN_BACKEND_VERSION: &str = "0.2.0";
+
+pub fn min_backend_version() -> semver::Version {
+    semver::Version::parse(MIN_BACKEND_VERSION).expect("valid MIN_BACKEND_VERSION")
+}
+
+/// Stderr lines kept for the error reported when the backend exits abnormally.
+const STDERR_TAIL_LINES: usize = 20;
+
+/// Managed as a `tokio::sync::Mutex`: async commands and background tasks lock
+/// it on Tokio worker threads, where a blocking mutex held across an `.await`
+/// could stall the worker and deadlock other tasks waiting on the sidecar.
+pub struct SidecarState {
+    pub child: Option<CommandChild>,
+    /// Restarts since the backend was last healthy.
+    pub restart_count: u32,
+    /// Signalled by the monitor task once the current child has exited.
+    terminated: Arc<Notify>,
+    /// Reported by the backend on a `VERSION:` stdout line.
+    pub version: Option<String>,
+    /// When the current child was spawned.
+    pub start_time: Option<Instant>,
+    /// Cancels the health watchdog of the current child.
+    watchdog: Option<CancellationToken>,
+    /// Recent resource usage samples, oldest first.
+    pub metrics: VecDeque<ProcessSample>,
+    /// Why the backend last failed to start or exited with an error.
+    pub last_error: Option<String>,
+}
+
+impl SidecarState {
+    pub fn new() -> Self {
+        Self {
+            child: None,
+            restart_count: 0,
+            terminated: Arc::new(Notify::new()),
+            version: None,
+            start_time: None,
+            watchdog: None,
+            metrics: VecDeque::with_capacity(METRICS_CAPACITY),
+            last_error: None,
+        }
+    }
+
+    pub fn push_metrics(&mut self, sample: ProcessSample) {
+        if self.metrics.len() == METRICS_CAPACITY {
+            self.metrics.pop_front();
+        }
+        self.metrics.push_back(sample);
+    }
+
+    fn stop_watchdog(&mut self) {
+        if let Some(token) = self.watchdog.take() {
+            token.cancel();
+        }
+    }
+}
+
+/// Backend version from `GET /version`, fetched once the health poll succeeds.
+pub struct BackendVersion(pub String);
+
+impl Default for BackendVersion {
+    fn default() -> Self {
+        Self("unknown".into())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AppVersion {
+    pub frontend: String,
+    pub backend: String,
+    /// Only known for CI builds.
+    pub commit: Option<String>,
+}
+
+pub fn env_path(app: &AppHandle) -> tauri::Result<PathBuf> {
+    Ok(app.path().app_config_dir()?.join(".env"))
+}
+
+/// Extra environment variables for the backend, loaded from `.env` in the app
+/// config directory. Values may be secrets, so only keys are ever logged.
+///
+/// The second map keeps what `.env` defined, for restoring variables the
+/// `env` table of `settings.toml` overrode once it no longer does.
+#[derive(Default)]
+pub struct SidecarEnv(pub HashMap<String, String>, HashMap<String, String>);
+
+impl SidecarEnv {
+    pub fn load(app: &AppHandle) -> Self {
+        let Ok(path) = env_path(app) else {
+            return Self::default();
+        };
+        let Ok(entries) = dotenvy::from_path_iter(&path) else {
+            return Self::default();
+        };
+
+        let mut vars = HashMap::new();
+        for entry in entries {
+            match entry {
+                Ok((key, value)) => {
+                    vars.insert(key, value);
+                }
+                Err(e) => log::warn!("Skipping invalid line in {}: {}", path.display(), e),
+            }
+        }
+        log::info!(
+            "Loaded {} sidecar environment variables from {}",
+            vars.len(),
+            path.display()
+        );
+        Self(vars.clone(), vars)
+    }
+
+    /// Swap the variables set by the `env` table of `settings.toml`, `old`,
+    /// for `new`, leaving the rest alone.
+    pub fn replace_config(&mut self, old: &HashMap<String, String>, new: &HashMap<String, String>) {
+        for key in old.keys() {
+            match self.1.get(key) {
+                Some(value) => self.0.insert(key.clone(), value.clone()),
+                None => self.0.remove(key),
+            };
+        }
+        self.0.extend(new.clone());
+    }
+}
+
+#[derive(Serialize)]
+pub struct SidecarStatus {
+    pub running: bool,
+    pub pid: Option<u32>,
+    pub port: Option<u16>,
+    pub uptime_secs: Option<u64>,
+    pub restart_count: u32,
+    pub last_error: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+struct SidecarError {
+    message: String,
+}
+
+/// Payload of the `backend-*` lifecycle events sent to the frontend.
+#[derive(Clone, Serialize)]
+#[serde(tag = "type")]
+pub enum SidecarEvent {
+    Ready {
+        port: u16,
+        pid: u32,
+        version: String,
+    },
+    Restarting {
+        attempt: u32,
+    },
+    Failed {
+        reason: String,
+    },
+}
+
+impl SidecarEvent {
+    fn name(&self) -> &'static str {
+        match self {
+            SidecarEvent::Ready { .. } => "backend-ready",
+            SidecarEvent::Restarting { .. } => "backend-restarting",
+            SidecarEvent::Failed { .. } => "backend-failed",
+        }
+    }
+
+    pub fn emit(self, handle: &AppHandle) {
+        #[cfg(desktop)]
+        crate::tray::set_healthy(handle, matches!(self, SidecarEvent::Ready { .. }));
+        let _ = handle.emit(self.name(), self);
+    }
+}
+
+/// A newline-delimited JSON object the backend wrote to stdout, or a message on
+/// its WebSocket, forwarded to the frontend as a `backend-message` event.
+#[derive(Serialize)]
+#[serde(transparent)]
+pub struct BackendMessage(serde_json::Value);
+
+impl BackendMessage {
+    pub fn parse(line: &str) -> Option<Self> {
+        match serde_json::from_str::<serde_json::Value>(line) {
+            Ok(value) if value.is_object() => Some(Self(value)),
+            _ => None,
+        }
+    }
+
+    pub fn emit(&self, handle: &AppHandle) {
+        let _ = handle.emit("backend-message", self);
+    }
+}
+
+/// How long to wait for the backend to answer `/health` after it is spawned.
+///
+/// Part of `AppConfig`, initially taken from `plugins.backend` in `tauri.conf.json`.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackendStartupConfig {
+    pub max_attempts: u32,
+    pub interval_ms: u64,
+    pub version_policy: VersionPolicy,
+}
+
+impl Default for BackendStartupConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 60,
+            interval_ms: 500,
+            version_policy: VersionPolicy::Warn,
+        }
+    }
+}
+
+/// What to do when the backend is older than `MIN_BACKEND_VERSION`.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum VersionPolicy {
+    /// Emit `version-mismatch` and keep running.
+    Warn,
+    /// Emit `version-mismatch` and stop the backend.
+    Kill,
+    Ignore,
+}
+
+#[derive(Clone, Serialize)]
+struct VersionMismatch {
+    required: String,
+    found: String,
+}
+
+/// How long the backend gets to exit on its own after `POST /shutdown`.
+///
+/// Part of `AppConfig`, initially taken from `plugins.backend` in `tauri.conf.json`.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShutdownConfig {
+    pub graceful_timeout_ms: u64,
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self {
+            graceful_timeout_ms: 3000,
+        }
+    }
+}
+
+/// Deserialize `plugins.backend` from `tauri.conf.json`, falling back to defaults.
+pub fn load_backend_config<T: DeserializeOwned + Default>(app: &AppHandle) -> T {
+    app.config()
+        .plugins
+        .0
+        .get("backend")
+        .and_then(|value| serde_json::from_value(value.clone()).ok())
+        .unwrap_or_default()
+}
+
+/// Name of the bundled backend binary. Universal macOS bundles ship one binary
+/// per architecture, so pick the one matching the running process.
+pub fn sidecar_name() -> &'static str {
+    if cfg!(target_os = "macos") {
+        match std::env::consts::ARCH {
+            "aarch64" => "echo-backend-aarch64",
+            _ => "echo-backend-x86_64",
+        }
+    } else {
+        "echo-backend"
+    }
+}
+
+/// Which binary to launch for the backend and what to pass it besides `--port`.
+///
+/// `tls` is read from `plugins.backend` in `tauri.conf.json` when present.
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct SidecarConfig {
+    #[serde(skip)]
+    pub name: String,
+    #[serde(skip)]
+    pub extra_args: Vec<String>,
+    /// Serve the backend over HTTPS with a locally issued certificate.
+    pub tls: bool,
+    /// Passed as `--locale`; the OS locale unless configured or set by the user.
+    pub locale: Option<String>,
+}
+
+impl Default for SidecarConfig {
+    fn default() -> Self {
+        Self {
+            name: sidecar_name().to_string(),
+            extra_args: vec!["--tauri".to_string()],
+            tls: false,
+            locale: sys_locale::get_locale().filter(|locale| is_valid_locale(locale)),
+        }
+    }
+}
+
+impl SidecarConfig {
+    /// `plugins.backend` from `tauri.conf.json`, with the user's locale choice on top.
+    pub fn load(app: &AppHandle) -> Self {
+        let mut config: Self = load_backend_config(app);
+        if let Some(locale) = app
+            .store(STORE_FILE)
+            .ok()
+            .and_then(|store| store.get(LOCALE_KEY))
+            .and_then(|value| value.as_str().map(String::from))
+        {
+            config.locale = Some(locale);
+        }
+        config
+    }
+}
+
+/// Loose BCP-47 check (`en`, `en-US`, `zh-Hans-CN`), enough to keep junk out
+/// of the backend's command line.
+pub fn is_valid_locale(locale: &str) -> bool {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN
+        .get_or_init(|| Regex::new(r"^[A-Za-z]{2,3}(-[A-Za-z0-9]{1,8})*$").expect("valid regex"))
+        .is_match(locale)
+}
+
+pub fn spawn_process(
+    config: &SidecarConfig,
+    handle: &AppHandle,
+    port: u16,
+    env: HashMap<String, String>,
+) -> Result<(Receiver<CommandEvent>, CommandChild), tauri_plugin_shell::Error> {
+    let mut command = handle
+        .shell()
+        .sidecar(&config.name)?
+        .args(["--port", &port.to_string()])
+        .args(&config.extra_args)
+        .envs(env);
+    if let Some(locale) = &config.locale {
+        command = command.args(["--locale", locale]);
+    }
+    if config.tls {
+        let paths = tls::paths(handle).map_err(std::io::Error::other)?;
+        command = command
+            .arg("--tls")
+            .arg("--cert")
+            .arg(&paths.cert)
+            .arg("--key")
+            .arg(&paths.key);
+    }
+    command.spawn()
+}
+
+/// Spawn the backend on `port`, then watch its output and poll it until healthy.
+///
+/// Returns a boxed future because spawn -> monitor -> restart -> spawn is async
+/// recursion, which an `async fn` can't express.
+pub fn spawn(
+    handle: &AppHandle,
+    port: u16,
+) -> Pin<Box<dyn Future<Output = Result<(), tauri_plugin_shell::Error>> + Send + '_>> {
+    Box::pin(async move {
+        let config = handle.state::<Mutex<SidecarConfig>>().lock().await.clone();
+        let env = handle.state::<Mutex<SidecarEnv>>().lock().await.0.clone();
+        let (rx, child) = match spawn_process(&config, handle, port, env) {
+            Ok(spawned) => spawned,
+            Err(e) => {
+                set_last_error(handle, format!("failed to start backend: {}", e)).await;
+                return Err(e);
+            }
+        };
+
+        // Store the child process for cleanup
+        let terminated = Arc::new(Notify::new());
+        {
+            let state = handle.state::<Mutex<SidecarState>>();
+            let mut guard = state.lock().await;
+            guard.child = Some(child);
+            guard.terminated = terminated.clone();
+            guard.version = None;
+            guard.start_time = Some(Instant::now());
+        }
+
+        tauri::async_runtime::spawn(monitor(handle.clone(), port, rx, terminated));
+        tauri::async_runtime::spawn(poll_health(handle.clone(), port));
+        Ok(())
+    })
+}
+
+/// Log sidecar output and restart it if it exits unexpectedly.
+async fn monitor(
+    handle: AppHandle,
+    port: u16,
+    mut rx: Receiver<CommandEvent>,
+    terminated: Arc<Notify>,
+) {
+    let mut stderr_tail = VecDeque::with_capacity(STDERR_TAIL_LINES);
+    while let Some(event) = rx.recv().await {
+        match event {
+            CommandEvent::Stdout(line) => {
+                let line = String::from_utf8_lossy(&line);
+                if let Some(message) = BackendMessage::parse(&line) {
+                    message.emit(&handle);
+                    continue;
+                }
+                log::info!("[sidecar] {}", line);
+                logs::record(&handle, LogLevel::Info, &line);
+                if let Some(version) = line.strip_prefix("VERSION:") {
+                    let state = handle.state::<Mutex<SidecarState>>();
+                    let mut guard = state.lock().await;
+                    if guard.version.is_none() {
+                        guard.version = Some(version.trim().to_string());
+                    }
+                }
+            }
+            CommandEvent::Stderr(line) => {
+                let line = String::from_utf8_lossy(&line);
+                log::warn!("[sidecar] {}", line);
+                logs::record(&handle, LogLevel::Warn, &line);
+                if stderr_tail.len() == STDERR_TAIL_LINES {
+                    stderr_tail.pop_front();
+                }
+                stderr_tail.push_back(line.trim_end().to_string());
+            }
+            CommandEvent::Terminated(status) => {
+                log::info!("[sidecar] terminated with status: {:?}", status);
+                terminated.notify_one();
+                // The child is taken out of the state when we kill it on purpose
+                let expected = {
+                    let state = handle.state::<Mutex<SidecarState>>();
+                    let mut guard = state.lock().await;
+                    guard.stop_watchdog();
+                    guard.child.take().is_none()
+                };
+                if !expected && status.code != Some(0) {
+                    let mut message = match status.code {
+                        Some(code) => format!("backend exited with code {}", code),
+                        None => "backend was terminated by a signal".to_string(),
+                    };
+                    for line in &stderr_tail {
+                        message.push('\n');
+                        message.push_str(line);
+                    }
+                    set_last_error(&handle, message).await;
+                    #[cfg(desktop)]
+                    crate::crash::on_backend_crash(&handle, status.code.unwrap_or(-1));
+                    restart(handle, port).await;
+                }
+                break;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Record why the backend failed and tell the frontend with `sidecar-error`.
+async fn set_last_error(handle: &AppHandle, message: String) {
+    handle
+        .state::<Mutex<SidecarState>>()
+        .lock()
+        .await
+        .last_error = Some(message.clone());
+    let _ = handle.emit("sidecar-error", SidecarError { message });
+}
+
+/// Re-spawn the backend with exponential back-off, giving up after `MAX_RESTARTS`.
+pub async fn restart(handle: AppHandle, port: u16) {
+    loop {
+        let attempt = {
+            let state = handle.state::<Mutex<SidecarState>>();
+            let mut guard = state.lock().await;
+            guard.restart_count += 1;
+            guard.restart_count
+        };
+        if attempt > MAX_RESTARTS {
+            log::error!("Backend crashed {} times in a row, giving up", MAX_RESTARTS);
+            SidecarEvent::Failed {
+                reason: format!("backend crashed {} times in a row", MAX_RESTARTS),
+            }
+            .emit(&handle);
+            return;
+        }
+
+        let delay = restart_delay(attempt);
+        log::warn!("Restarting backend in {:?} (attempt {})", delay, attempt);
+        SidecarEvent::Restarting { attempt }.emit(&handle);
+        tokio::time::sleep(delay).await;
+
+        match spawn(&handle, port).await {
+            Ok(()) => return,
+            Err(e) => log::error!("Failed to respawn sidecar: {}", e),
+        }
+    }
+}
+
+/// Doubles from 100 ms with every attempt, up to 30 s.
+fn restart_delay(attempt: u32) -> Duration {
+    Duration::from_millis(100u64.saturating_mul(2u64.saturating_pow(attempt.saturating_sub(1))))
+        .min(Duration::from_secs(30))
+}
+
+/// Poll the health endpoint until the backend is ready.
+async fn poll_health(handle: AppHandle, port: u16) {
+    let config = handle
+        .state::<Mutex<BackendStartupConfig>>()
+        .lock()
+        .await
+        .clone();
+    let backend = handle.state::<BackendClient>().proxy(port);
+
+    for i in 0..config.max_attempts {
+        match backend.health().await {
+            Ok(()) => {
+                log::info!("Backend ready on port {} (attempt {})", port, i + 1);
+                startup::mark(&handle, startup::Phase::BackendHealthy);
+                let (pid, version) = {
+                    let state = handle.state::<Mutex<SidecarState>>();
+                    let mut guard = state.lock().await;
+                    guard.restart_count = 0;
+                    (
+                        guard
+                            .child
+                            .as_ref()
+                            .map(CommandChild::pid)
+                            .unwrap_or_default(),
+                        guard.version.clone().unwrap_or_else(|| "unknown".into()),
+                    )
+                };
+                PortPreference::save(&handle, port);
+                migration::migrate_if_updated(&handle, &backend).await;
+                SidecarEvent::Ready { port, pid, version }.emit(&handle);
+                tauri::async_runtime::spawn(command_queue::replay(handle.clone()));
+
+                let watchdog_config = handle.state::<WatchdogConfig>();
+                let token = watchdog::start_watchdog(
+                    handle.clone(),
+                    port,
+                    Duration::from_millis(watchdog_config.watchdog_interval_ms),
+                    watchdog_config.watchdog_threshold,
+                );
+                if let Some(old) = handle
+                    .state::<Mutex<SidecarState>>()
+                    .lock()
+                    .await
+                    .watchdog
+                    .replace(token)
+                {
+                    old.cancel();
+                }
+
+                match backend.version().await {
+                    Ok(version) => {
+                        *handle.state::<Mutex<BackendVersion>>().lock().await =
+                            BackendVersion(version.clone());
+                        if config.version_policy != VersionPolicy::Ignore {
+                            check_version(&handle, &version, config.version_policy).await;
+                        }
+                    }
+                    Err(e) => log::warn!("Could not determine backend version: {}", e),
+                }
+                return;
+            }
+            Err(_) => {
+                tokio::time::sleep(Duration::from_millis(config.interval_ms)).await;
+            }
+        }
+    }
+    log::error!(
+        "Backend failed to start within {} ms",
+        u64::from(config.max_attempts) * config.interval_ms
+    );
+    // Still waiting on a migration: show the window so the failure is visible
+    if migration::pending(&handle) {
+        windows::show_main(&handle);
+    }
+}
+
+/// Compare the backend version against `MIN_BACKEND_VERSION` and apply `policy`.
+async fn check_version(handle: &AppHandle, found: &str, policy: VersionPolicy) {
+    let found = match semver::Version::parse(found) {
+        Ok(version) => version,
+        Err(e) => {
+            log::warn!("Backend reported invalid version {:?}: {}", found, e);
+            return;
+        }
+    };
+    let required = min_backend_version();
+    if found >= required {
+        return;
+    }
+    log::warn!(
+        "Backend version {} is older than required {}",
+        found,
+        required
+    );
+    let _ = handle.emit(
+        "version-mismatch",
+        VersionMismatch {
+            required: required.to_string(),
+            found: found.to_string(),
+        },
+    );
+    // Instances started with `spawn_sidecar` are left alone
+    if policy == VersionPolicy::Kill {
+        shutdown_default(handle).await;
+    }
+}
+
+/// Override the startup poll, e.g. so integration tests can fail fast.
+#[tauri::command]
+pub async fn set_startup_timeout(config: BackendStartupConfig, handle: AppHandle) {
+    *handle.state::<Mutex<BackendStartupConfig>>().lock().await = config;
+}
+
+#[tauri::command]
+pub async fn check_backend_health(
+    sidecar_state: State<'_, Mutex<SidecarState>>,
+    port_state: State<'_, Mutex<PortState>>,
+    client: State<'_, BackendClient>,
+) -> Result<bool, EchoError> {
+    if sidecar_state.lock().await.child.is_none() {
+        return Err(EchoError::SidecarNotRunning);
+    }
+    let port = port_state
+        .lock()
+        .await
+        .0
+        .ok_or(EchoError::SidecarNotRunning)?;
+
+    Ok(client.proxy(port).health().await.is_ok())
+}
+
+/// Take the child out of the state so its exit isn't treated as a crash.
+pub async fn take_child(handle: &AppHandle) -> Option<(CommandChild, Arc<Notify>)> {
+    let state = handle.state::<Mutex<SidecarState>>();
+    let mut guard = state.lock().await;
+    guard.stop_watchdog();
+    let child = guard.child.take()?;
+    Some((child, guard.terminated.clone()))
+}
+
+/// Ask the backend to exit via `POST /shutdown` and wait for `exited`.
+///
+/// Returns `false` if it needs killing: it didn't accept the request, or it
+/// is still running after the timeout.
+pub async fn graceful_shutdown(
+    backend: &BackendProxy<impl Transport>,
+    exited: impl Future<Output = ()>,
+    config: &ShutdownConfig,
+) -> bool {
+    if let Err(e) = backend.shutdown().await {
+        log::warn!("Backend did not accept shutdown request, killing it: {}", e);
+        return false;
+    }
+
+    let timeout = Duration::from_millis(config.graceful_timeout_ms);
+    if tokio::time::timeout(timeout, exited).await.is_err() {
+        log::info!("Backend still running after {:?}, killing it", timeout);
+        return false;
+    }
+    true
+}
+
+/// Stop every backend, the default one and any started with `spawn_sidecar`.
+pub async fn shutdown(handle: &AppHandle) {
+    shutdown_default(handle).await;
+    registry::kill_all(handle).await;
+}
+
+/// Stop the default backend, gracefully if its port is known.
+pub async fn shutdown_default(handle: &AppHandle) {
+    let Some((child, terminated)) = take_child(handle).await else {
+        return;
+    };
+    let port = handle.state::<Mutex<PortState>>().lock().await.0;
+    let config = handle.state::<ShutdownConfig>();
+    let exited = match port {
+        Some(port) => {
+            let backend = handle.state::<BackendClient>().proxy(port);
+            graceful_shutdown(&backend, terminated.notified(), &config).await
+        }
+        None => false,
+    };
+    if !exited {
+        let _ = child.kill();
+    }
+}
+
+/// Stop the backend (and its watchdog) gracefully, then exit the app.
+#[tauri::command]
+pub async fn quit_app(app: AppHandle) {
+    shutdown(&app).await;
+    app.exit(0);
+}
+
+/// The process side of `restart_sidecar`, so it can be tested without a
+/// backend binary.
+pub trait Sidecar {
+    /// Kill the running backend, if any, and wait for it to exit.
+    fn stop(&self) -> impl Future<Output = Result<(), EchoError>> + Send;
+
+    fn choose_port(&self) -> Result<u16, EchoError>;
+
+    /// Start the backend on `port`.
+    fn start(&self, port: u16) -> impl Future<Output = Result<(), EchoError>> + Send;
+}
+
+impl Sidecar for AppHandle {
+    async fn stop(&self) -> Result<(), EchoError> {
+        let Some((child, terminated)) = take_child(self).await else {
+            return Ok(());
+        };
+        log::info!("Restarting sidecar on request");
+        child
+            .kill()
+            .map_err(|e| format!("failed to kill sidecar: {}", e))?;
+        tokio::time::timeout(Duration::from_secs(5), terminated.notified())
+            .await
+            .map_err(|_| "timed out waiting for sidecar to exit".to_string())?;
+        Ok(())
+    }
+
+    fn choose_port(&self) -> Result<u16, EchoError> {
+        Ok(port::choose_port(self)?)
+    }
+
+    async fn start(&self, port: u16) -> Result<(), EchoError> {
+        spawn(self, port)
+            .await
+            .map_err(|e| format!("failed to spawn sidecar: {}", e).into())
+    }
+}
+
+/// Kill the running backend, wait for it to exit, and start it again on a fresh port.
+#[tauri::command]
+pub async fn restart_sidecar(
+    state: State<'_, Mutex<SidecarState>>,
+    port_state: State<'_, Mutex<PortState>>,
+    handle: AppHandle,
+) -> Result<(), EchoError> {
+    restart_with(&handle, &state, &port_state).await
+}
+
+async fn restart_with(
+    sidecar: &impl Sidecar,
+    state: &Mutex<SidecarState>,
+    port_state: &Mutex<PortState>,
+) -> Result<(), EchoError> {
+    sidecar.stop().await?;
+    let port = sidecar.choose_port()?;
+    port_state.lock().await.0 = Some(port);
+    state.lock().await.restart_count = 0;
+    sidecar.start(port).await
+}
+
+#[tauri::command]
+pub async fn get_sidecar_status(handle: AppHandle) -> SidecarStatus {
+    let port = handle.state::<Mutex<PortState>>().lock().await.0;
+    let state = handle.state::<Mutex<SidecarState>>();
+    let guard = state.lock().await;
+    let running = guard.child.is_some();
+    SidecarStatus {
+        running,
+        pid: guard.child.as_ref().map(CommandChild::pid),
+        port,
+        uptime_secs: guard
+            .start_time
+            .filter(|_| running)
+            .map(|start| start.elapsed().as_secs()),
+        restart_count: guard.restart_count,
+        last_error: guard.last_error.clone(),
+    }
+}
+
+#[tauri::command]
+pub async fn get_app_version(app: AppHandle) -> AppVersion {
+    AppVersion {
+        frontend: app.package_info().version.to_string(),
+        backend: app.state::<Mutex<BackendVersion>>().lock().await.0.clone(),
+        commit: option_env!("GITHUB_SHA").map(String::from),
+    }
+}
+
+/// Remember `locale` for the backend and restart it so it takes effect.
+#[tauri::command]
+pub async fn set_locale(
+    locale: String,
+    state: State<'_, Mutex<SidecarState>>,
+    port_state: State<'_, Mutex<PortState>>,
+    handle: AppHandle,
+) -> Result<(), EchoError> {
+    if !is_valid_locale(&locale) {
+        return Err(EchoError::invalid(format!("invalid locale {:?}", locale)));
+    }
+    let store = handle.store(STORE_FILE).map_err(|e| e.to_string())?;
+    store.set(LOCALE_KEY, locale.as_str());
+    store.save().map_err(|e| e.to_string())?;
+
+    log::info!("Switching backend locale to {}", locale);
+    handle.state::<Mutex<SidecarConfig>>().lock().await.locale = Some(locale);
+    restart_sidecar(state, port_state, handle).await
+}
+
+/// Set an environment variable for the backend; applies from its next (re)start.
+#[tauri::command]
+pub async fn set_sidecar_env(key: String, value: String, handle: AppHandle) {
+    log::info!("Updated sidecar environment variable {}", key);
+    handle
+        .state::<Mutex<SidecarEnv>>()
+        .lock()
+        .await
+        .0
+        .insert(key, value);
+}
+
+/// Write `message` plus a newline to the backend's stdin.
+#[tauri::command]
+pub async fn send_to_sidecar(
+    message: String,
+    state: State<'_, Mutex<SidecarState>>,
+) -> Result<(), EchoError> {
+    let mut guard = state.lock().await;
+    let child = guard.child.as_mut().ok_or(EchoError::SidecarNotRunning)?;
+    child
+        .write(format!("{}\n", message).as_bytes())
+        .map_err(|e| EchoError::io(format!("failed to write to sidecar stdin: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend_proxy::MockTransport;
+
+    fn config() -> ShutdownConfig {
+        ShutdownConfig {
+            graceful_timeout_ms: 50,
+        }
+    }
+
+    #[test]
+    fn restart_delay_doubles_up_to_a_cap() {
+        assert_eq!(restart_delay(1), Duration::from_millis(100));
+        assert_eq!(restart_delay(2), Duration::from_millis(200));
+        assert_eq!(restart_delay(5), Duration::from_millis(1600));
+        assert_eq!(restart_delay(10), Duration::from_secs(30));
+        assert_eq!(restart_delay(u32::MAX), Duration::from_secs(30));
+    }
+
+    /// Records what `restart_with` asks of it. A `stuck` backend won't stop.
+    #[derive(Default)]
+    struct MockSidecar {
+        stuck: bool,
+        calls: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl Sidecar for MockSidecar {
+        async fn stop(&self) -> Result<(), EchoError> {
+            self.calls.lock().unwrap().push("stop".to_string());
+            if self.stuck {
+                return Err("timed out waiting for sidecar to exit".into());
+            }
+            Ok(())
+        }
+
+        fn choose_port(&self) -> Result<u16, EchoError> {
+            Ok(8123)
+        }
+
+        async fn start(&self, port: u16) -> Result<(), EchoError> {
+            self.calls.lock().unwrap().push(format!("start {}", port));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn restart_starts_again_on_a_fresh_port() {
+        let sidecar = MockSidecar::default();
+        let mut state = SidecarState::new();
+        state.restart_count = 3;
+        let state = Mutex::new(state);
+        let port_state = Mutex::new(PortState(Some(8000)));
+
+        restart_with(&sidecar, &state, &port_state).await.unwrap();
+        assert_eq!(*sidecar.calls.lock().unwrap(), ["stop", "start 8123"]);
+        assert_eq!(port_state.lock().await.0, Some(8123));
+        assert_eq!(state.lock().await.restart_count, 0);
+    }
+
+    #[tokio::test]
+    async fn restart_does_not_start_a_second_backend() {
+        let sidecar = MockSidecar {
+            stuck: true,
+            ..Default::default()
+        };
+        let state = Mutex::new(SidecarState::new());
+        let port_state = Mutex::new(PortState(Some(8000)));
+
+        assert!(restart_with(&sidecar, &state, &port_state).await.is_err());
+        assert_eq!(*sidecar.calls.lock().unwrap(), ["stop"]);
+        assert_eq!(port_state.lock().await.0, Some(8000));
+    }
+
+    #[tokio::test]
+    async fn graceful_shutdown_waits_for_exit() {
+        let backend =
+            BackendProxy::new(MockTransport::default().respond("POST", "/shutdown", 200, "{}"));
+        assert!(graceful_shutdown(&backend, async {}, &config()).await);
+    }
+
+    #[tokio::test]
+    async fn graceful_shutdown_gives_up_after_timeout() {
+        let backend =
+            BackendProxy::new(MockTransport::default().respond("POST", "/shutdown", 200, "{}"));
+        let never = std::future::pending::<()>();
+        assert!(!graceful_shutdown(&backend, never, &config()).await);
+    }
+
+    #[tokio::test]
+    async fn graceful_shutdown_kills_when_rejected() {
+        // Would hang for the timeout if the request failing didn't end it
+        let slow = ShutdownConfig {
+            graceful_timeout_ms: 60_000,
+        };
+        let never = std::future::pending::<()>();
+
+        let failing =
+            BackendProxy::new(MockTransport::default().respond("POST", "/shutdown", 500, ""));
+        assert!(!graceful_shutdown(&failing, never, &slow).await);
+
+        let down = BackendProxy::new(MockTransport::default());
+        assert!(!graceful_shutdown(&down, std::future::pending(), &slow).await);
+    }
+
+    #[test]
+    fn min_backend_version_is_valid() {
+        min_backend_version();
+    }
+
+    #[test]
+    fn locales_are_loosely_validated() {
+        for locale in ["en", "en-US", "zh-Hans-CN", "de-CH-1996", "fil-PH"] {
+            assert!(is_valid_locale(locale), "{locale}");
+        }
+        for locale in [
+            "",
+            "e",
+            "en_US",
+            "en-",
+            "en US",
+            "-US",
+            "en-US;rm -rf",
+            "toolongregion-x",
+        ] {
+            assert!(!is_valid_locale(locale), "{locale}");
+        }
+    }
+
+    #[test]
+    fn config_env_changes_leave_other_variables_alone() {
+        let dotenv = HashMap::from([
+            ("HF_HOME".to_string(), "/models".to_string()),
+            ("LOG_FORMAT".to_string(), "text".to_string()),
+        ]);
+        let mut env = SidecarEnv(dotenv.clone(), dotenv);
+        env.0.insert("SET_AT_RUNTIME".into(), "1".into());
+        let old = HashMap::from([
+            ("LOG_FORMAT".to_string(), "json".to_string()),
+            ("API_BASE".to_string(), "https://a".to_string()),
+        ]);
+        env.replace_config(&HashMap::new(), &old);
+        assert_eq!(env.0["LOG_FORMAT"], "json");
+
+        let new = HashMap::from([("DEBUG".to_string(), "1".to_string())]);
+        env.replace_config(&old, &new);
+        assert_eq!(
+            env.0,
+            HashMap::from([
+                ("HF_HOME".to_string(), "/models".to_string()),
+                ("LOG_FORMAT".to_string(), "text".to_string()),
+                ("SET_AT_RUNTIME".to_string(), "1".to_string()),
+                ("DEBUG".to_string(), "1".to_string()),
+            ])
+        );
+    }
+}
@@ -0,0 +1,33 @@
+use tauri::AppHandle;
+
+use crate::sidecar;
+
+/// Resolves on Ctrl+C, or on SIGTERM as well on Unix (`kill`, `systemctl stop`).
+pub async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = terminate.recv() => {}
+                }
+                return;
+            }
+            Err(e) => log::warn!("Failed to listen for SIGTERM: {}", e),
+        }
+    }
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// Stop the backend and exit when the process is asked to terminate, since no
+/// window gets a `Destroyed` event in that case and the sidecar would be orphaned.
+pub fn exit_on_shutdown_signal(handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        shutdown_signal().await;
+        log::info!("Received shutdown signal, stopping sidecar");
+        sidecar::shutdown(&handle).await;
+        handle.exit(0);
+    });
+}
@@ -0,0 +1,101 @@
+//! How long startup took, split into phases, for spotting regressions. The
+//! clock starts when `run` is entered; the config windows are created, and
+//! usually shown by `windows::show_main_plugin`, before `setup` runs. After an
+//! update the main window is only shown once the migration finished.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::plugin::TauriPlugin;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+/// Milliseconds from the start of `run` until each phase finished.
+#[derive(Clone, Copy, Serialize)]
+pub struct StartupTiming {
+    /// Until the last phase finished.
+    pub total_ms: u64,
+    pub sidecar_spawn_ms: u64,
+    pub health_poll_ms: u64,
+    pub window_shown_ms: u64,
+}
+
+pub enum Phase {
+    SidecarSpawned,
+    BackendHealthy,
+    WindowShown,
+}
+
+#[derive(Default)]
+struct Phases {
+    sidecar_spawned: Option<Duration>,
+    backend_healthy: Option<Duration>,
+    window_shown: Option<Duration>,
+    timing: Option<StartupTiming>,
+}
+
+pub struct StartupTimer {
+    start: Instant,
+    phases: Mutex<Phases>,
+}
+
+impl StartupTimer {
+    pub fn start() -> Self {
+        Self {
+            start: Instant::now(),
+            phases: Mutex::default(),
+        }
+    }
+}
+
+/// Record that `phase` finished, unless it already did (the backend becomes
+/// healthy again after every restart). Emits `startup-timing` once all have.
+pub fn mark<R: Runtime>(app: &AppHandle<R>, phase: Phase) {
+    let timer = app.state::<StartupTimer>();
+    let mut phases = timer.phases.lock().unwrap();
+    let slot = match phase {
+        Phase::SidecarSpawned => &mut phases.sidecar_spawned,
+        Phase::BackendHealthy => &mut phases.backend_healthy,
+        Phase::WindowShown => &mut phases.window_shown,
+    };
+    if slot.is_some() {
+        return;
+    }
+    *slot = Some(timer.start.elapsed());
+
+    let (Some(sidecar_spawn), Some(health_poll), Some(window_shown)) = (
+        phases.sidecar_spawned,
+        phases.backend_healthy,
+        phases.window_shown,
+    ) else {
+        return;
+    };
+    let ms = |duration: Duration| duration.as_millis() as u64;
+    let timing = StartupTiming {
+        total_ms: ms(sidecar_spawn.max(health_poll).max(window_shown)),
+        sidecar_spawn_ms: ms(sidecar_spawn),
+        health_poll_ms: ms(health_poll),
+        window_shown_ms: ms(window_shown),
+    };
+    phases.timing = Some(timing);
+    log::info!("Startup took {} ms", timing.total_ms);
+    let _ = app.emit("startup-timing", timing);
+}
+
+/// Marks the main window as shown where nothing holds it back, i.e. on
+/// mobile; `windows::show_main` marks it on desktop.
+pub fn plugin<R: Runtime>() -> TauriPlugin<R> {
+    tauri::plugin::Builder::new("startup-timing")
+        .on_window_ready(|window| {
+            if window.label() == "main" && window.is_visible().unwrap_or(false) {
+                mark(window.app_handle(), Phase::WindowShown);
+            }
+        })
+        .build()
+}
+
+/// `None` until every phase finished.
+#[tauri::command]
+pub fn get_startup_timing(timer: State<'_, StartupTimer>) -> Option<StartupTiming> {
+    timer.phases.lock().unwrap().timing
+}
@@ -0,0 +1,54 @@
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+use sysinfo::System;
+use tauri::{AppHandle, Manager};
+
+/// Machine details for support requests, collected once per session.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SystemInfo {
+    pub os: String,
+    pub os_version: String,
+    pub arch: String,
+    pub cpu_count: usize,
+    pub total_memory_kb: u64,
+    pub locale: Option<String>,
+    pub app_data_dir: Option<String>,
+    pub log_dir: Option<String>,
+}
+
+#[derive(Default)]
+pub struct SystemInfoCache(OnceLock<SystemInfo>);
+
+fn collect(app: &AppHandle) -> SystemInfo {
+    let mut sys = System::new();
+    sys.refresh_memory();
+    let path = app.path();
+    SystemInfo {
+        os: std::env::consts::OS.to_string(),
+        os_version: System::long_os_version().unwrap_or_else(|| "unknown".into()),
+        arch: std::env::consts::ARCH.to_string(),
+        cpu_count: std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1),
+        total_memory_kb: sys.total_memory() / 1024,
+        locale: sys_locale::get_locale(),
+        app_data_dir: path
+            .app_data_dir()
+            .ok()
+            .map(|dir| dir.display().to_string()),
+        log_dir: path.app_log_dir().ok().map(|dir| dir.display().to_string()),
+    }
+}
+
+pub fn system_info(app: &AppHandle) -> SystemInfo {
+    app.state::<SystemInfoCache>()
+        .0
+        .get_or_init(|| collect(app))
+        .clone()
+}
+
+#[tauri::command]
+pub fn get_system_info(app: AppHandle) -> SystemInfo {
+    system_info(&app)
+}
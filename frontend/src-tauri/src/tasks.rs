@@ -0,0 +1,234 @@
+//! Recurring backend calls on a cron schedule, such as a sync every 30
+//! minutes, that run whether or not the frontend is open. Each trigger sends
+//! the task's payload to `POST /tasks/{action}`. Tasks are kept in the store
+//! and scheduled again on startup.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+use tauri_plugin_store::StoreExt;
+use tokio::sync::Mutex;
+use tokio_cron_scheduler::{Job, JobScheduler, JobSchedulerError};
+use uuid::Uuid;
+
+use crate::backend_proxy::BackendClient;
+use crate::error::EchoError;
+use crate::port::PortState;
+use crate::STORE_FILE;
+
+const TASKS_KEY: &str = "scheduled_tasks";
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ScheduledTask {
+    /// Assigned by `schedule_task`; whatever the frontend sends is ignored.
+    #[serde(default)]
+    pub id: Uuid,
+    /// With seconds, e.g. `0 */30 * * * *` for every 30 minutes.
+    pub cron: String,
+    /// Name of the backend endpoint under `/tasks/`.
+    pub action: String,
+    #[serde(default)]
+    pub payload: serde_json::Value,
+    #[serde(default)]
+    pub paused: bool,
+}
+
+/// The scheduler and every task, with the job id of those that aren't paused.
+pub struct TaskScheduler {
+    scheduler: JobScheduler,
+    tasks: HashMap<Uuid, (ScheduledTask, Option<Uuid>)>,
+}
+
+fn scheduler_error(e: JobSchedulerError) -> EchoError {
+    EchoError::Other {
+        message: format!("task scheduler failed: {}", e),
+    }
+}
+
+impl TaskScheduler {
+    /// Start the scheduler with the tasks saved in the store.
+    pub async fn load(app: &AppHandle) -> Result<Self, EchoError> {
+        let scheduler = JobScheduler::new().await.map_err(scheduler_error)?;
+        scheduler.start().await.map_err(scheduler_error)?;
+
+        let mut this = Self {
+            scheduler,
+            tasks: HashMap::new(),
+        };
+        this.schedule_saved(app).await;
+        Ok(this)
+    }
+
+    /// Replace every task with those in the store, after it was cleared or
+    /// replaced.
+    pub async fn reload(&mut self, app: &AppHandle) -> Result<(), EchoError> {
+        let ids: Vec<Uuid> = self.tasks.keys().copied().collect();
+        for id in ids {
+            self.remove_job(id).await?;
+        }
+        self.tasks.clear();
+        self.schedule_saved(app).await;
+        Ok(())
+    }
+
+    async fn schedule_saved(&mut self, app: &AppHandle) {
+        let saved: Vec<ScheduledTask> = app
+            .store(STORE_FILE)
+            .ok()
+            .and_then(|store| store.get(TASKS_KEY))
+            .and_then(|value| serde_json::from_value(value).ok())
+            .unwrap_or_default();
+        for task in saved {
+            let job = if task.paused {
+                None
+            } else {
+                self.add_job(app, &task)
+                    .await
+                    .inspect_err(|e| log::warn!("Failed to schedule task {}: {}", task.id, e))
+                    .ok()
+            };
+            self.tasks.insert(task.id, (task, job));
+        }
+    }
+
+    async fn add_job(&self, app: &AppHandle, task: &ScheduledTask) -> Result<Uuid, EchoError> {
+        let (handle, action, payload) = (app.clone(), task.action.clone(), task.payload.clone());
+        let job = Job::new_async(task.cron.as_str(), move |_, _| {
+            let (handle, action, payload) = (handle.clone(), action.clone(), payload.clone());
+            Box::pin(async move {
+                if let Err(e) = run(&handle, &action, &payload).await {
+                    log::warn!("Scheduled task {} failed: {}", action, e);
+                }
+            })
+        })
+        .map_err(|e| {
+            EchoError::invalid(format!("invalid cron expression {:?}: {}", task.cron, e))
+        })?;
+        self.scheduler.add(job).await.map_err(scheduler_error)
+    }
+
+    async fn remove_job(&mut self, id: Uuid) -> Result<(), EchoError> {
+        let (_, job) = self
+            .tasks
+            .get_mut(&id)
+            .ok_or_else(|| EchoError::invalid(format!("no task {}", id)))?;
+        if let Some(job) = job.take() {
+            self.scheduler.remove(&job).await.map_err(scheduler_error)?;
+        }
+        Ok(())
+    }
+
+    fn persist(&self, app: &AppHandle) -> Result<(), EchoError> {
+        let tasks: Vec<&ScheduledTask> = self.tasks.values().map(|(task, _)| task).collect();
+        let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+        store.set(TASKS_KEY, serde_json::to_value(tasks)?);
+        store
+            .save()
+            .map_err(|e| format!("failed to save scheduled tasks: {}", e).into())
+    }
+}
+
+async fn run(app: &AppHandle, action: &str, payload: &serde_json::Value) -> Result<(), EchoError> {
+    let port = app.state::<Mutex<PortState>>().lock().await.0;
+    app.state::<BackendClient>()
+        .current(port)?
+        .run_task(action, payload)
+        .await
+}
+
+#[tauri::command]
+pub async fn schedule_task(
+    mut task: ScheduledTask,
+    app: AppHandle,
+    scheduler: State<'_, Mutex<TaskScheduler>>,
+) -> Result<Uuid, EchoError> {
+    if task.action.is_empty()
+        || !task
+            .action
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(EchoError::invalid(format!(
+            "invalid action {:?}",
+            task.action
+        )));
+    }
+    task.id = Uuid::new_v4();
+
+    let mut scheduler = scheduler.lock().await;
+    let job = if task.paused {
+        None
+    } else {
+        Some(scheduler.add_job(&app, &task).await?)
+    };
+    let id = task.id;
+    scheduler.tasks.insert(id, (task, job));
+    scheduler.persist(&app)?;
+    Ok(id)
+}
+
+#[tauri::command]
+pub async fn cancel_task(
+    id: Uuid,
+    app: AppHandle,
+    scheduler: State<'_, Mutex<TaskScheduler>>,
+) -> Result<(), EchoError> {
+    let mut scheduler = scheduler.lock().await;
+    scheduler.remove_job(id).await?;
+    scheduler.tasks.remove(&id);
+    scheduler.persist(&app)
+}
+
+/// Stop triggering a task but keep it, until `resume_task`.
+#[tauri::command]
+pub async fn pause_task(
+    id: Uuid,
+    app: AppHandle,
+    scheduler: State<'_, Mutex<TaskScheduler>>,
+) -> Result<(), EchoError> {
+    let mut scheduler = scheduler.lock().await;
+    scheduler.remove_job(id).await?;
+    if let Some((task, _)) = scheduler.tasks.get_mut(&id) {
+        task.paused = true;
+    }
+    scheduler.persist(&app)
+}
+
+#[tauri::command]
+pub async fn resume_task(
+    id: Uuid,
+    app: AppHandle,
+    scheduler: State<'_, Mutex<TaskScheduler>>,
+) -> Result<(), EchoError> {
+    let mut scheduler = scheduler.lock().await;
+    let task = match scheduler.tasks.get(&id) {
+        Some((task, None)) => task.clone(),
+        Some((_, Some(_))) => return Ok(()),
+        None => return Err(EchoError::invalid(format!("no task {}", id))),
+    };
+    let job = scheduler.add_job(&app, &task).await?;
+    if let Some(entry) = scheduler.tasks.get_mut(&id) {
+        *entry = (
+            ScheduledTask {
+                paused: false,
+                ..task
+            },
+            Some(job),
+        );
+    }
+    scheduler.persist(&app)
+}
+
+#[tauri::command]
+pub async fn list_tasks(
+    scheduler: State<'_, Mutex<TaskScheduler>>,
+) -> Result<Vec<ScheduledTask>, EchoError> {
+    Ok(scheduler
+        .lock()
+        .await
+        .tasks
+        .values()
+        .map(|(task, _)| task.clone())
+        .collect())
+}
@@ -0,0 +1,65 @@
+//! Scratch files the frontend hands to other programs, kept in an app-specific
+//! directory under the system temp dir and deleted when the app exits.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use tauri::{AppHandle, Manager, State};
+
+use crate::error::EchoError;
+
+/// Files written by `write_temp_file` that haven't been cleaned up yet.
+#[derive(Default)]
+pub struct TempFileRegistry(Mutex<HashSet<PathBuf>>);
+
+impl TempFileRegistry {
+    /// Delete every registered file, returning how many were removed.
+    fn cleanup(&self) -> u32 {
+        let mut removed = 0;
+        for path in self.0.lock().unwrap().drain() {
+            match std::fs::remove_file(&path) {
+                Ok(()) => removed += 1,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => log::warn!("Failed to remove {}: {}", path.display(), e),
+            }
+        }
+        removed
+    }
+}
+
+/// Write `contents` to a temp file called `name` and return its full path.
+/// `name` must be a plain file name; an existing file of that name is replaced.
+#[tauri::command]
+pub fn write_temp_file(
+    name: String,
+    contents: Vec<u8>,
+    app: AppHandle,
+    registry: State<'_, TempFileRegistry>,
+) -> Result<String, EchoError> {
+    let file_name = Path::new(&name)
+        .file_name()
+        .filter(|file_name| *file_name == name.as_str())
+        .ok_or_else(|| EchoError::invalid(format!("invalid file name {:?}", name)))?;
+
+    let dir = std::env::temp_dir().join(&app.config().identifier);
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(file_name);
+    std::fs::write(&path, contents)?;
+    registry.0.lock().unwrap().insert(path.clone());
+    Ok(path.to_string_lossy().into_owned())
+}
+
+/// Delete the files written by `write_temp_file`, returning how many were removed.
+#[tauri::command]
+pub fn cleanup_temp_files(registry: State<'_, TempFileRegistry>) -> u32 {
+    registry.cleanup()
+}
+
+/// Called when the app exits.
+pub fn cleanup(app: &AppHandle) {
+    let removed = app.state::<TempFileRegistry>().cleanup();
+    if removed > 0 {
+        log::info!("Removed {} temp files", removed);
+    }
+}
@@ -0,0 +1,127 @@
+//! Light, dark or system theme plus an optional accent colour, switchable
+//! without a restart. The native theme is applied to each window as it is
+//! created, before it is shown; the accent is set as the `--accent-color` CSS
+//! variable on every page load.
+
+use serde::{Deserialize, Serialize};
+use tauri::plugin::TauriPlugin;
+use tauri::webview::PageLoadEvent;
+use tauri::{AppHandle, Emitter, Manager, Runtime, WebviewWindow};
+use tauri_plugin_store::StoreExt;
+
+use crate::error::EchoError;
+use crate::STORE_FILE;
+
+const THEME_KEY: &str = "theme";
+
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeMode {
+    Light,
+    Dark,
+    /// Follow the OS setting.
+    #[default]
+    System,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Theme {
+    pub mode: ThemeMode,
+    /// A hex colour such as `#3b82f6`.
+    pub accent_color: Option<String>,
+}
+
+impl Theme {
+    pub fn load<R: Runtime>(app: &AppHandle<R>) -> Self {
+        app.store(STORE_FILE)
+            .ok()
+            .and_then(|store| store.get(THEME_KEY))
+            .and_then(|value| serde_json::from_value(value).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn native(&self) -> Option<tauri::Theme> {
+        match self.mode {
+            ThemeMode::Light => Some(tauri::Theme::Light),
+            ThemeMode::Dark => Some(tauri::Theme::Dark),
+            ThemeMode::System => None,
+        }
+    }
+
+    /// Switch every window to this theme.
+    pub fn apply<R: Runtime>(&self, app: &AppHandle<R>) -> tauri::Result<()> {
+        app.set_theme(self.native());
+        for window in app.webview_windows().values() {
+            self.apply_accent(window)?;
+        }
+        Ok(())
+    }
+
+    fn apply_accent<R: Runtime>(&self, window: &WebviewWindow<R>) -> tauri::Result<()> {
+        let style = "document.documentElement.style";
+        let script = match &self.accent_color {
+            Some(color) => format!(
+                "{}.setProperty('--accent-color', {})",
+                style,
+                serde_json::Value::from(color.as_str())
+            ),
+            None => format!("{}.removeProperty('--accent-color')", style),
+        };
+        window.eval(script)
+    }
+}
+
+fn is_hex_color(color: &str) -> bool {
+    color.strip_prefix('#').is_some_and(|hex| {
+        matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit())
+    })
+}
+
+/// Applies the saved theme to new windows and sets the accent colour again
+/// whenever a page finishes loading. Must be registered before
+/// `windows::show_main_plugin` so the main window never shows unthemed.
+pub fn plugin<R: Runtime>() -> TauriPlugin<R> {
+    tauri::plugin::Builder::new("theme")
+        .on_window_ready(|window| {
+            if let Err(e) = window.set_theme(Theme::load(window.app_handle()).native()) {
+                log::warn!("Failed to apply theme to {}: {}", window.label(), e);
+            }
+        })
+        .on_page_load(|webview, payload| {
+            if payload.event() != PageLoadEvent::Finished {
+                return;
+            }
+            let Some(window) = webview.app_handle().get_webview_window(webview.label()) else {
+                return;
+            };
+            if let Err(e) = Theme::load(webview.app_handle()).apply_accent(&window) {
+                log::warn!("Failed to apply accent color: {}", e);
+            }
+        })
+        .build()
+}
+
+/// Switch every window to `theme`, remember it and emit `theme-changed`.
+#[tauri::command]
+pub fn apply_theme(theme: Theme, app: AppHandle) -> Result<(), EchoError> {
+    if let Some(color) = &theme.accent_color {
+        if !is_hex_color(color) {
+            return Err(EchoError::invalid(format!(
+                "invalid accent color {:?}",
+                color
+            )));
+        }
+    }
+
+    theme
+        .apply(&app)
+        .map_err(|e| format!("failed to apply accent color: {}", e))?;
+
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    store.set(THEME_KEY, serde_json::to_value(&theme)?);
+    store
+        .save()
+        .map_err(|e| format!("failed to save theme: {}", e))?;
+    let _ = app.emit("theme-changed", theme);
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use std::error::Error;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use rcgen::{BasicConstraints, CertificateParams, DnType, IsCa, Issuer, KeyPair, KeyUsagePurpose};
+use tauri::{AppHandle, Manager};
+use tokio::sync::Mutex;
+
+use crate::error::EchoError;
+use crate::sidecar::{self, SidecarConfig};
+
+/// Certificates for serving the backend over HTTPS, in `tls/` under the app
+/// data directory. The client trusts a local CA rather than the backend
+/// certificate itself, so the backend certificate can be rotated without
+/// rebuilding the client.
+pub struct TlsPaths {
+    pub ca_cert: PathBuf,
+    pub ca_key: PathBuf,
+    pub cert: PathBuf,
+    pub key: PathBuf,
+}
+
+pub fn paths(app: &AppHandle) -> tauri::Result<TlsPaths> {
+    let dir = app.path().app_data_dir()?.join("tls");
+    Ok(TlsPaths {
+        ca_cert: dir.join("ca.pem"),
+        ca_key: dir.join("ca-key.pem"),
+        cert: dir.join("backend.pem"),
+        key: dir.join("backend-key.pem"),
+    })
+}
+
+fn ca_params() -> CertificateParams {
+    let mut params = CertificateParams::default();
+    params
+        .distinguished_name
+        .push(DnType::CommonName, "Echo Local CA");
+    params.is_ca = IsCa::Ca(BasicConstraints::Constrained(0));
+    params.key_usages = vec![KeyUsagePurpose::KeyCertSign, KeyUsagePurpose::CrlSign];
+    params
+}
+
+/// Create the CA and backend certificate if they don't exist yet and return
+/// the CA certificate as PEM.
+pub fn ensure(app: &AppHandle) -> Result<Vec<u8>, Box<dyn Error>> {
+    let paths = paths(app)?;
+    fs::create_dir_all(paths.ca_cert.parent().expect("tls dir"))?;
+
+    if !paths.ca_cert.exists() || !paths.ca_key.exists() {
+        log::info!("Generating local CA for backend TLS");
+        let key = KeyPair::generate()?;
+        let cert = ca_params().self_signed(&key)?;
+        write_private(&paths.ca_key, &key.serialize_pem())?;
+        fs::write(&paths.ca_cert, cert.pem())?;
+        issue_backend_cert(&paths)?;
+    } else if !paths.cert.exists() || !paths.key.exists() {
+        issue_backend_cert(&paths)?;
+    }
+    Ok(fs::read(&paths.ca_cert)?)
+}
+
+/// Sign a fresh key pair for the backend with the local CA.
+fn issue_backend_cert(paths: &TlsPaths) -> Result<(), Box<dyn Error>> {
+    log::info!("Issuing backend TLS certificate");
+    let ca_key = KeyPair::from_pem(&fs::read_to_string(&paths.ca_key)?)?;
+    let issuer = Issuer::new(ca_params(), ca_key);
+
+    let key = KeyPair::generate()?;
+    let mut params = CertificateParams::new(vec!["127.0.0.1".into(), "localhost".into()])?;
+    params
+        .distinguished_name
+        .push(DnType::CommonName, "Echo backend");
+    let cert = params.signed_by(&key, &issuer)?;
+
+    write_private(&paths.key, &key.serialize_pem())?;
+    fs::write(&paths.cert, cert.pem())?;
+    Ok(())
+}
+
+fn write_private(path: &Path, contents: &str) -> std::io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)?.write_all(contents.as_bytes())
+}
+
+/// Replace the backend certificate and key, then restart the backend on them.
+#[tauri::command]
+pub async fn rotate_tls_cert(app: AppHandle) -> Result<(), EchoError> {
+    if !app.state::<Mutex<SidecarConfig>>().lock().await.tls {
+        return Err(EchoError::invalid("TLS is not enabled for the backend"));
+    }
+    let paths = paths(&app).map_err(|e| e.to_string())?;
+    issue_backend_cert(&paths).map_err(|e| format!("failed to issue certificate: {}", e))?;
+    sidecar::restart_sidecar(app.state(), app.state(), app.clone()).await
+}
@@ -0,0 +1,119 @@
+use tauri::image::Image;
+use tauri::menu::{CheckMenuItem, Menu, MenuItem};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::{App, AppHandle, Manager, Wry};
+
+use crate::{sidecar, windows};
+
+const TRAY_ID: &str = "main";
+const ICON_HEALTHY: &[u8] = include_bytes!("../icons/tray-green.png");
+const ICON_STARTING: &[u8] = include_bytes!("../icons/tray-yellow.png");
+
+/// The "Always on Top" item, kept to update its check mark.
+struct AlwaysOnTopItem(CheckMenuItem<Wry>);
+
+pub fn setup_tray(app: &mut App) -> tauri::Result<()> {
+    let show = MenuItem::with_id(app, "show", "Show", true, None::<&str>)?;
+    let hide = MenuItem::with_id(app, "hide", "Hide", true, None::<&str>)?;
+    let always_on_top = CheckMenuItem::with_id(
+        app,
+        "always_on_top",
+        "Always on Top",
+        true,
+        windows::saved_always_on_top(app.handle()),
+        None::<&str>,
+    )?;
+    let restart = MenuItem::with_id(app, "restart", "Restart Backend", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+    let menu = Menu::with_items(app, &[&show, &hide, &always_on_top, &restart, &quit])?;
+    app.manage(AlwaysOnTopItem(always_on_top));
+
+    TrayIconBuilder::with_id(TRAY_ID)
+        .icon(Image::from_bytes(ICON_STARTING)?)
+        .tooltip("Echo")
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                restore_hidden(tray.app_handle());
+            }
+        })
+        .on_menu_event(|app, event| match event.id().as_ref() {
+            "show" => {
+                if let Err(e) = windows::focus_window(None, app.clone()) {
+                    log::warn!("Failed to show window from tray: {}", e);
+                }
+            }
+            "hide" => {
+                if let Err(e) = windows::hide_window(None, app.clone()) {
+                    log::warn!("Failed to hide window from tray: {}", e);
+                }
+            }
+            "always_on_top" => {
+                // The item has already toggled its own check mark
+                let item = &app.state::<AlwaysOnTopItem>().0;
+                let enabled = item.is_checked().unwrap_or(false);
+                let result = match app.get_webview_window("main") {
+                    Some(window) => windows::set_always_on_top(enabled, window.as_ref().window()),
+                    None => Ok(()),
+                };
+                if let Err(e) = result {
+                    log::warn!("Failed to set always on top from tray: {}", e);
+                }
+            }
+            "restart" => {
+                let handle = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    set_healthy(&handle, false);
+                    if let Err(e) =
+                        sidecar::restart_sidecar(handle.state(), handle.state(), handle.clone())
+                            .await
+                    {
+                        log::error!("Failed to restart backend from tray: {}", e);
+                    }
+                });
+            }
+            "quit" => {
+                tauri::async_runtime::spawn(sidecar::quit_app(app.clone()));
+            }
+            _ => {}
+        })
+        .build(app)?;
+    Ok(())
+}
+
+/// Bring the main window back after `minimize_to_tray`. Not reported on Linux,
+/// where the "Show" menu item does the same.
+fn restore_hidden(app: &AppHandle) {
+    let hidden = app
+        .get_webview_window("main")
+        .is_some_and(|window| !window.is_visible().unwrap_or(true));
+    if hidden {
+        if let Err(e) = windows::focus_window(None, app.clone()) {
+            log::warn!("Failed to restore window from tray: {}", e);
+        }
+    }
+}
+
+/// Show a green icon while the backend is healthy, yellow while it is starting
+/// or unhealthy.
+pub fn set_healthy(app: &AppHandle, healthy: bool) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    let bytes = if healthy { ICON_HEALTHY } else { ICON_STARTING };
+    if let Err(e) = Image::from_bytes(bytes).and_then(|icon| tray.set_icon(Some(icon))) {
+        log::warn!("Failed to update tray icon: {}", e);
+    }
+}
+
+pub fn set_always_on_top_checked(app: &AppHandle, checked: bool) {
+    if let Some(item) = app.try_state::<AlwaysOnTopItem>() {
+        let _ = item.0.set_checked(checked);
+    }
+}
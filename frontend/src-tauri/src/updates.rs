@@ -0,0 +1,204 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_store::StoreExt;
+use tauri_plugin_updater::{Update, Updater, UpdaterExt};
+use tokio_util::sync::CancellationToken;
+
+use crate::error::EchoError;
+use crate::logs::now_ms;
+use crate::STORE_FILE;
+
+/// Overrides the endpoints from `tauri.conf.json`, e.g. to point at a mock server.
+const ENDPOINT_ENV: &str = "ECHO_UPDATER_ENDPOINT";
+
+/// Milliseconds since the epoch of the last background or manual check.
+const LAST_CHECK_KEY: &str = "last_update_check";
+
+/// How often to look for updates in the background.
+///
+/// Read from `plugins.backend` in `tauri.conf.json` when present.
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct UpdateConfig {
+    pub update_check_interval_ms: u64,
+}
+
+impl Default for UpdateConfig {
+    fn default() -> Self {
+        Self {
+            update_check_interval_ms: 24 * 60 * 60 * 1000,
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+struct UpdateAvailable {
+    version: String,
+    notes: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct UpdateInfo {
+    pub available: bool,
+    pub version: Option<String>,
+    pub notes: Option<String>,
+    pub pub_date: Option<String>,
+}
+
+impl From<Option<&Update>> for UpdateInfo {
+    fn from(update: Option<&Update>) -> Self {
+        Self {
+            available: update.is_some(),
+            version: update.map(|u| u.version.clone()),
+            notes: update.and_then(|u| u.body.clone()),
+            pub_date: update.and_then(|u| pub_date(&u.raw_json)),
+        }
+    }
+}
+
+/// `pub_date` from the update manifest, which the plugin doesn't expose as text.
+fn pub_date(manifest: &serde_json::Value) -> Option<String> {
+    manifest.get("pub_date")?.as_str().map(String::from)
+}
+
+/// How long to wait before the next background check, given when the last one
+/// ran (`0` if never).
+fn next_check_in(interval: Duration, last_ms: u64, now_ms: u64) -> Duration {
+    interval.saturating_sub(Duration::from_millis(now_ms.saturating_sub(last_ms)))
+}
+
+fn updater(app: &AppHandle) -> Result<Updater, String> {
+    let mut builder = app.updater_builder();
+    if let Ok(endpoint) = std::env::var(ENDPOINT_ENV) {
+        let url = tauri::Url::parse(&endpoint).map_err(|e| e.to_string())?;
+        builder = builder.endpoints(vec![url]).map_err(|e| e.to_string())?;
+    }
+    builder.build().map_err(|e| e.to_string())
+}
+
+pub async fn check(app: &AppHandle) -> Result<Option<Update>, String> {
+    updater(app)?.check().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_check(app: AppHandle) -> Result<UpdateInfo, EchoError> {
+    let update = check(&app).await?;
+    Ok(UpdateInfo::from(update.as_ref()))
+}
+
+/// Download and install the pending update; the frontend relaunches afterwards.
+#[tauri::command]
+pub async fn apply_update(app: AppHandle) -> Result<(), EchoError> {
+    let update = check(&app).await?.ok_or("no update available")?;
+    log::info!("Installing update {}", update.version);
+    update
+        .download_and_install(|_, _| {}, || {})
+        .await
+        .map_err(|e| e.to_string().into())
+}
+
+/// Check now, remember when, and emit `update-available` if there is one.
+async fn check_and_notify(app: &AppHandle) -> Result<UpdateInfo, String> {
+    let update = check(app).await?;
+    match app.store(STORE_FILE) {
+        Ok(store) => {
+            store.set(LAST_CHECK_KEY, now_ms());
+            if let Err(e) = store.save() {
+                log::warn!("Failed to persist update check time: {}", e);
+            }
+        }
+        Err(e) => log::warn!("Failed to open store: {}", e),
+    }
+    if let Some(update) = &update {
+        log::info!("Update {} is available", update.version);
+        let _ = app.emit(
+            "update-available",
+            UpdateAvailable {
+                version: update.version.clone(),
+                notes: update.body.clone(),
+            },
+        );
+    }
+    Ok(UpdateInfo::from(update.as_ref()))
+}
+
+/// Cancels the background update checks.
+pub struct PeriodicUpdateCheck(pub CancellationToken);
+
+/// Check for updates every `interval` until cancelled. The last check time is
+/// kept in the store, so restarting the app doesn't reset the schedule.
+pub fn start_periodic_checks(app: AppHandle, interval: Duration) -> PeriodicUpdateCheck {
+    let token = CancellationToken::new();
+    let cancelled = token.clone();
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let last = app
+                .store(STORE_FILE)
+                .ok()
+                .and_then(|store| store.get(LAST_CHECK_KEY))
+                .and_then(|value| value.as_u64())
+                .unwrap_or_default();
+            tokio::select! {
+                _ = cancelled.cancelled() => return,
+                _ = tokio::time::sleep(next_check_in(interval, last, now_ms())) => {}
+            }
+            if let Err(e) = check_and_notify(&app).await {
+                log::warn!("Background update check failed: {}", e);
+                // Don't retry in a tight loop against an unreachable server
+                tokio::select! {
+                    _ = cancelled.cancelled() => return,
+                    _ = tokio::time::sleep(interval) => {}
+                }
+            }
+        }
+    });
+
+    PeriodicUpdateCheck(token)
+}
+
+/// Check for updates right away, e.g. from a "Check for updates" button.
+#[tauri::command]
+pub async fn trigger_update_check(app: AppHandle) -> Result<UpdateInfo, EchoError> {
+    check_and_notify(&app).await.map_err(EchoError::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const HOUR: Duration = Duration::from_secs(60 * 60);
+
+    #[test]
+    fn pub_date_comes_from_the_manifest() {
+        let manifest = json!({
+            "version": "0.3.0",
+            "notes": "Fixes",
+            "pub_date": "2026-10-01T12:00:00Z",
+        });
+        assert_eq!(pub_date(&manifest).as_deref(), Some("2026-10-01T12:00:00Z"));
+        assert_eq!(pub_date(&json!({ "version": "0.3.0" })), None);
+        assert_eq!(pub_date(&json!({ "pub_date": 1 })), None);
+    }
+
+    #[test]
+    fn first_check_runs_right_away() {
+        assert_eq!(next_check_in(HOUR, 0, 1_000_000_000_000), Duration::ZERO);
+    }
+
+    #[test]
+    fn next_check_waits_for_the_rest_of_the_interval() {
+        let last = 1_000_000_000_000;
+        let now = last + 15 * 60 * 1000;
+        assert_eq!(next_check_in(HOUR, last, now), Duration::from_secs(45 * 60));
+    }
+
+    #[test]
+    fn clock_going_backwards_waits_a_full_interval() {
+        let last = 1_000_000_000_000;
+        assert_eq!(next_check_in(HOUR, last, last - 1000), HOUR);
+    }
+}
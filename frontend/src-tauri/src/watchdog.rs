@@ -0,0 +1,129 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio_util::sync::CancellationToken;
+
+use crate::backend_proxy::{BackendClient, BackendProxy, Transport};
+use crate::sidecar;
+
+/// How often the watchdog polls `/health` once the backend is up, and how many
+/// misses in a row mean it has hung.
+///
+/// Part of `AppConfig`, initially taken from `plugins.backend` in `tauri.conf.json`.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchdogConfig {
+    pub watchdog_interval_ms: u64,
+    pub watchdog_threshold: u32,
+    /// Kill and restart the backend once it is considered unhealthy.
+    pub watchdog_restart: bool,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            watchdog_interval_ms: 10_000,
+            watchdog_threshold: 3,
+            watchdog_restart: true,
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+struct BackendUnhealthy {
+    failures: u32,
+}
+
+/// Poll `/health` every `interval` until cancelled, emitting `backend-unhealthy`
+/// after `threshold` consecutive failures.
+pub fn start_watchdog(
+    handle: AppHandle,
+    port: u16,
+    interval: Duration,
+    threshold: u32,
+) -> CancellationToken {
+    let token = CancellationToken::new();
+    let cancelled = token.clone();
+
+    tauri::async_runtime::spawn(async move {
+        let backend = handle.state::<BackendClient>().proxy(port);
+        let failures = tokio::select! {
+            _ = cancelled.cancelled() => return,
+            failures = until_unhealthy(&backend, interval, threshold) => failures,
+        };
+
+        log::error!("Backend missed {} health checks in a row", failures);
+        let _ = handle.emit("backend-unhealthy", BackendUnhealthy { failures });
+        #[cfg(desktop)]
+        crate::tray::set_healthy(&handle, false);
+        if handle.state::<WatchdogConfig>().watchdog_restart {
+            if let Some((child, terminated)) = sidecar::take_child(&handle).await {
+                let _ = child.kill();
+                // Otherwise its monitor could take the new child out of
+                // the state when the old one's `Terminated` arrives
+                if tokio::time::timeout(Duration::from_secs(5), terminated.notified())
+                    .await
+                    .is_err()
+                {
+                    log::error!("Timed out waiting for the hung backend to exit");
+                    return;
+                }
+            }
+            sidecar::restart(handle, port).await;
+        }
+    });
+
+    token
+}
+
+/// Poll `/health` every `interval` until it failed `threshold` times in a row,
+/// and return how many times that was.
+async fn until_unhealthy<T: Transport>(
+    backend: &BackendProxy<T>,
+    interval: Duration,
+    threshold: u32,
+) -> u32 {
+    let mut failures = 0;
+    loop {
+        tokio::time::sleep(interval).await;
+        match backend.health().await {
+            Ok(()) => failures = 0,
+            Err(_) => failures += 1,
+        }
+        if failures >= threshold {
+            return failures;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend_proxy::MockTransport;
+
+    const INTERVAL: Duration = Duration::from_millis(1);
+
+    #[tokio::test]
+    async fn unhealthy_after_threshold_misses() {
+        let backend =
+            BackendProxy::new(MockTransport::default().respond("GET", "/health", 503, ""));
+        assert_eq!(until_unhealthy(&backend, INTERVAL, 3).await, 3);
+        assert_eq!(backend_requests(&backend), 3);
+    }
+
+    #[tokio::test]
+    async fn healthy_backend_is_left_alone() {
+        let backend =
+            BackendProxy::new(MockTransport::default().respond("GET", "/health", 200, "{}"));
+        let polled = tokio::time::timeout(
+            Duration::from_millis(50),
+            until_unhealthy(&backend, INTERVAL, 3),
+        );
+        assert!(polled.await.is_err());
+    }
+
+    fn backend_requests(backend: &BackendProxy<MockTransport>) -> usize {
+        backend.transport().requests().len()
+    }
+}
@@ -0,0 +1,515 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use serde::Serialize;
+use tauri::plugin::TauriPlugin;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State, WebviewWindow, Window};
+#[cfg(desktop)]
+use tauri_plugin_store::StoreExt;
+use tokio::sync::Mutex;
+
+use crate::app_config::AppConfig;
+use crate::error::EchoError;
+#[cfg(desktop)]
+use crate::STORE_FILE;
+
+/// Counts open windows so closing a secondary window doesn't take the backend down.
+#[derive(Clone, Default)]
+pub struct MultiWindowSidecarGuard(Arc<AtomicUsize>);
+
+impl MultiWindowSidecarGuard {
+    pub fn window_opened(&self) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Returns `true` if the closed window was the last one. Windows that were
+    /// never counted leave the count at zero.
+    pub fn window_closed(&self) -> bool {
+        self.0
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |open| {
+                open.checked_sub(1)
+            })
+            == Ok(1)
+    }
+}
+
+/// Registers every window with the guard as it is created, including the ones
+/// declared in `tauri.conf.json`.
+pub fn guard_plugin<R: Runtime>() -> TauriPlugin<R> {
+    tauri::plugin::Builder::new("window-guard")
+        .on_window_ready(|window| {
+            window.state::<MultiWindowSidecarGuard>().window_opened();
+        })
+        .build()
+}
+
+/// Call `shutdown` if `window` was the last one open.
+pub fn on_window_destroyed<R: Runtime>(window: &Window<R>, shutdown: impl FnOnce(&AppHandle<R>)) {
+    if window.state::<MultiWindowSidecarGuard>().window_closed() {
+        shutdown(window.app_handle());
+    }
+}
+
+/// Brings the main window to the front, restoring it if minimized or hidden.
+pub fn focus_main<R: Runtime>(app: &AppHandle<R>) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = focus(&window);
+    }
+}
+
+fn focus<R: Runtime>(window: &WebviewWindow<R>) -> tauri::Result<()> {
+    window.unminimize()?;
+    window.show()?;
+    window.set_focus()
+}
+
+fn window_by_label<R: Runtime>(
+    app: &AppHandle<R>,
+    label: Option<String>,
+) -> Result<WebviewWindow<R>, EchoError> {
+    let label = label.unwrap_or_else(|| "main".into());
+    app.get_webview_window(&label)
+        .ok_or_else(|| EchoError::invalid(format!("no window with label {:?}", label)))
+}
+
+/// Show, restore and focus the window `label` (`main` by default).
+#[tauri::command]
+pub fn focus_window(label: Option<String>, app: AppHandle) -> Result<(), EchoError> {
+    let window = window_by_label(&app, label)?;
+    focus(&window).map_err(|e| format!("failed to focus window: {}", e).into())
+}
+
+#[tauri::command]
+pub fn hide_window(label: Option<String>, app: AppHandle) -> Result<(), EchoError> {
+    let window = window_by_label(&app, label)?;
+    window
+        .hide()
+        .map_err(|e| format!("failed to hide window: {}", e).into())
+}
+
+#[derive(Clone, Serialize)]
+struct NewInstance {
+    args: Vec<String>,
+    cwd: String,
+}
+
+/// Keeps a second launch from spawning another backend on the same port.
+/// The new process exits straight away and its arguments are handed to the
+/// running one as a `new-instance` event; `echo://` links in them are also
+/// delivered through the deep link handler.
+///
+/// Must be registered before `tauri_plugin_shell` so the second instance
+/// exits before it gets a chance to spawn a sidecar.
+#[cfg(desktop)]
+pub fn single_instance_plugin<R: Runtime>() -> TauriPlugin<R> {
+    tauri_plugin_single_instance::init(on_another_instance)
+}
+
+#[cfg(desktop)]
+fn on_another_instance<R: Runtime>(app: &AppHandle<R>, args: Vec<String>, cwd: String) {
+    log::info!("Another instance was launched with {:?}", args);
+    focus_main(app);
+    let _ = app.emit("new-instance", NewInstance { args, cwd });
+}
+
+/// Remembers window size and position across launches. Visibility is left
+/// out: quitting while minimized to the tray would save the window as hidden,
+/// and the next launch would bring it back invisible.
+#[cfg(desktop)]
+pub fn window_state_plugin<R: Runtime>() -> TauriPlugin<R> {
+    window_state_builder().build()
+}
+
+#[cfg(desktop)]
+fn window_state_builder() -> tauri_plugin_window_state::Builder {
+    tauri_plugin_window_state::Builder::new().with_state_flags(saved_state_flags())
+}
+
+/// The plugin only writes its file on a clean exit, so the geometry is saved
+/// on every move and resize too, in case the app is killed.
+#[cfg(desktop)]
+pub fn save_geometry<R: Runtime>(window: &Window<R>, event: &tauri::WindowEvent) {
+    use tauri_plugin_window_state::AppHandleExt;
+
+    if matches!(
+        event,
+        tauri::WindowEvent::Resized(_) | tauri::WindowEvent::Moved(_)
+    ) {
+        if let Err(e) = window.app_handle().save_window_state(saved_state_flags()) {
+            log::warn!("Failed to save window state: {}", e);
+        }
+    }
+}
+
+#[cfg(desktop)]
+fn saved_state_flags() -> tauri_plugin_window_state::StateFlags {
+    tauri_plugin_window_state::StateFlags::all() - tauri_plugin_window_state::StateFlags::VISIBLE
+}
+
+/// The main window starts hidden (`visible: false` in `tauri.conf.json`) and
+/// is shown here once `window_state_plugin`, which must be registered first,
+/// has applied the saved geometry, so there's no visible jump on startup.
+///
+/// After an update the window is only shown once the backend has migrated its
+/// data (`migration::migrate_if_updated`), so the app can't be used before.
+#[cfg(desktop)]
+pub fn show_main_plugin<R: Runtime>() -> TauriPlugin<R> {
+    tauri::plugin::Builder::new("show-main")
+        .on_window_ready(|window| {
+            if window.label() == "main" && !crate::migration::pending(window.app_handle()) {
+                show_main(window.app_handle());
+            }
+        })
+        .build()
+}
+
+/// Show the main window for the first time.
+pub fn show_main<R: Runtime>(app: &AppHandle<R>) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    if let Err(e) = window.show().and_then(|_| window.set_focus()) {
+        log::warn!("Failed to show the main window: {}", e);
+    }
+    crate::startup::mark(app, crate::startup::Phase::WindowShown);
+}
+
+/// Store key for the "Always on Top" preference of the main window.
+#[cfg(desktop)]
+const ALWAYS_ON_TOP_KEY: &str = "always_on_top";
+
+#[cfg(desktop)]
+pub fn saved_always_on_top<R: Runtime>(app: &AppHandle<R>) -> bool {
+    app.store(STORE_FILE)
+        .ok()
+        .and_then(|store| store.get(ALWAYS_ON_TOP_KEY))
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false)
+}
+
+/// Puts the main window back on top if it was when the app last ran. Must be
+/// registered after `show_main_plugin` so the window is already shown,
+/// which some Wayland compositors need before they honour it.
+#[cfg(desktop)]
+pub fn always_on_top_plugin<R: Runtime>() -> TauriPlugin<R> {
+    tauri::plugin::Builder::new("always-on-top")
+        .on_window_ready(|window| {
+            if window.label() == "main" && saved_always_on_top(window.app_handle()) {
+                if let Err(e) = window.set_always_on_top(true) {
+                    log::warn!("Failed to restore always on top: {}", e);
+                }
+            }
+        })
+        .build()
+}
+
+/// Keep `window` above other windows, remembering the choice and updating the
+/// tray menu's check mark.
+#[cfg(desktop)]
+#[tauri::command]
+pub fn set_always_on_top(enabled: bool, window: Window) -> Result<(), EchoError> {
+    window
+        .set_always_on_top(enabled)
+        .map_err(|e| format!("failed to set always on top: {}", e))?;
+
+    let app = window.app_handle();
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    store.set(ALWAYS_ON_TOP_KEY, enabled);
+    store.save().map_err(|e| e.to_string())?;
+    crate::tray::set_always_on_top_checked(app, enabled);
+    Ok(())
+}
+
+/// Store key for whether closing the main window hides it to the tray.
+#[cfg(desktop)]
+const MINIMIZE_TO_TRAY_KEY: &str = "minimize_to_tray";
+
+#[cfg(desktop)]
+pub fn saved_minimize_to_tray<R: Runtime>(app: &AppHandle<R>) -> bool {
+    app.store(STORE_FILE)
+        .ok()
+        .and_then(|store| store.get(MINIMIZE_TO_TRAY_KEY))
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false)
+}
+
+/// Whether closing the main window should `minimize_to_tray` instead.
+#[cfg(desktop)]
+#[tauri::command]
+pub fn set_minimize_to_tray(enabled: bool, app: AppHandle) -> Result<(), EchoError> {
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    store.set(MINIMIZE_TO_TRAY_KEY, enabled);
+    store.save().map_err(|e| e.to_string().into())
+}
+
+/// Hide `window`, leaving the app in the tray, and tell the user where it went.
+#[cfg(desktop)]
+#[tauri::command]
+pub async fn minimize_to_tray(window: Window, app: AppHandle) -> Result<(), EchoError> {
+    window
+        .hide()
+        .map_err(|e| format!("failed to hide window: {}", e))?;
+    if let Err(e) = crate::notifications::show_notification(
+        "Echo is running in the background".into(),
+        "Click the tray icon to restore".into(),
+        None,
+        app,
+    )
+    .await
+    {
+        log::warn!("Failed to show minimize to tray notification: {}", e);
+    }
+    Ok(())
+}
+
+/// Forget the saved window geometry and put the main window back at its
+/// configured size, e.g. after the monitor it was on got disconnected.
+#[cfg(desktop)]
+#[tauri::command]
+pub fn reset_window_state(app: AppHandle) -> Result<(), EchoError> {
+    use tauri::LogicalSize;
+    use tauri_plugin_window_state::AppHandleExt;
+
+    let path = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| e.to_string())?
+        .join(app.filename());
+    match std::fs::remove_file(&path) {
+        Ok(()) => log::info!("Removed saved window state {}", path.display()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => {
+            return Err(EchoError::io(format!(
+                "failed to remove {}: {}",
+                path.display(),
+                e
+            )))
+        }
+    }
+
+    // The plugin keeps its own copy of the state and writes it back on exit,
+    // so the window itself has to be reset too
+    let (Some(window), Some(config)) = (
+        app.get_webview_window("main"),
+        app.config().app.windows.first(),
+    ) else {
+        return Ok(());
+    };
+    window
+        .unmaximize()
+        .and_then(|_| window.set_size(LogicalSize::new(config.width, config.height)))
+        .and_then(|_| window.center())
+        .map_err(|e| e.to_string().into())
+}
+
+/// Show `title` after the configured `title_prefix`, e.g. "Echo — exam.yaml".
+/// Control characters are dropped; an empty title leaves just the prefix.
+#[tauri::command]
+pub async fn set_window_title(
+    title: String,
+    window: Window,
+    config: State<'_, Mutex<AppConfig>>,
+) -> Result<(), EchoError> {
+    let title: String = title.chars().filter(|c| !c.is_control()).collect();
+    let prefix = config.lock().await.title_prefix.clone();
+    let full = match title.trim() {
+        "" => prefix,
+        title => format!("{} — {}", prefix, title),
+    };
+    window
+        .set_title(&full)
+        .map_err(|e| format!("failed to set window title: {}", e).into())
+}
+
+/// Smallest size `resize_window` will set, in physical pixels.
+const MIN_WINDOW_SIZE: (u32, u32) = (400, 300);
+
+#[derive(Clone, Serialize)]
+struct WindowResized {
+    width: u32,
+    height: u32,
+}
+
+/// Resize `window` to `width`×`height` physical pixels, kept between
+/// `MIN_WINDOW_SIZE` and the work area of the monitor it is on. Emits
+/// `window-resized` with the size actually set.
+#[tauri::command]
+pub fn resize_window(width: u32, height: u32, window: Window) -> Result<(), EchoError> {
+    let mut width = width.max(MIN_WINDOW_SIZE.0);
+    let mut height = height.max(MIN_WINDOW_SIZE.1);
+    if let Some(monitor) = window
+        .current_monitor()
+        .map_err(|e| format!("failed to get the current monitor: {}", e))?
+    {
+        let work_area = monitor.work_area().size;
+        width = width.min(work_area.width);
+        height = height.min(work_area.height);
+    }
+    window
+        .set_size(tauri::PhysicalSize::new(width, height))
+        .map_err(|e| format!("failed to resize window: {}", e))?;
+    let _ = window.emit("window-resized", WindowResized { width, height });
+    Ok(())
+}
+
+/// Reload the frontend of `window` without touching the backend, after
+/// emitting `webview-reloading` to it so it can stash in-progress state. Debug
+/// builds clear the browsing data first so changed assets are picked up.
+#[tauri::command]
+pub fn reload_webview(window: WebviewWindow) -> Result<(), EchoError> {
+    #[cfg(debug_assertions)]
+    if let Err(e) = window.clear_all_browsing_data() {
+        log::warn!("Failed to clear browsing data before reload: {}", e);
+    }
+    let _ = window.emit_to(window.label(), "webview-reloading", ());
+    // Reloading from script queues it behind the event above; `reload()`
+    // could beat the listener to it
+    window
+        .eval("window.location.reload()")
+        .map_err(|e| format!("failed to reload window: {}", e).into())
+}
+
+/// Show `count` on the dock or taskbar icon (macOS, and Linux desktops with
+/// Unity launcher support); `None` or `0` hides it. Does nothing on Windows.
+#[cfg(not(target_os = "windows"))]
+#[tauri::command]
+pub fn set_badge_count(count: Option<u32>, window: Window) -> Result<(), EchoError> {
+    window
+        .set_badge_count(count.filter(|&count| count > 0).map(i64::from))
+        .map_err(|e| format!("failed to set badge count: {}", e).into())
+}
+
+#[cfg(target_os = "windows")]
+#[tauri::command]
+pub fn set_badge_count(_count: Option<u32>, _window: Window) -> Result<(), EchoError> {
+    Ok(())
+}
+
+#[tauri::command]
+pub fn clear_badge(window: Window) -> Result<(), EchoError> {
+    set_badge_count(None, window)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sidecar_survives_until_last_window_closes() {
+        let guard = MultiWindowSidecarGuard::default();
+        guard.window_opened();
+        guard.window_opened();
+        assert!(!guard.window_closed());
+        assert!(guard.window_closed());
+    }
+
+    #[test]
+    fn uncounted_windows_do_not_wrap_the_count() {
+        let guard = MultiWindowSidecarGuard::default();
+        assert!(!guard.window_closed());
+        guard.window_opened();
+        assert!(guard.window_closed());
+    }
+
+    #[test]
+    fn sidecar_survives_the_first_of_two_windows_closing() {
+        let app = tauri::test::mock_builder()
+            .plugin(guard_plugin())
+            .manage(MultiWindowSidecarGuard::default())
+            .build(tauri::test::mock_context(tauri::test::noop_assets()))
+            .unwrap();
+        let open = |label| {
+            tauri::WebviewWindowBuilder::new(&app, label, tauri::WebviewUrl::default())
+                .build()
+                .unwrap()
+        };
+        let (main, second) = (open("main"), open("second"));
+
+        let mut shut_down = false;
+        on_window_destroyed(&second.as_ref().window(), |_| shut_down = true);
+        assert!(!shut_down);
+        on_window_destroyed(&main.as_ref().window(), |_| shut_down = true);
+        assert!(shut_down);
+    }
+
+    #[test]
+    fn guard_clones_share_the_count() {
+        let guard = MultiWindowSidecarGuard::default();
+        guard.clone().window_opened();
+        assert!(guard.window_closed());
+    }
+
+    #[cfg(desktop)]
+    #[test]
+    fn window_geometry_is_saved_but_not_visibility() {
+        use tauri_plugin_window_state::StateFlags;
+
+        let flags = saved_state_flags();
+        for saved in [
+            StateFlags::SIZE,
+            StateFlags::POSITION,
+            StateFlags::MAXIMIZED,
+            StateFlags::FULLSCREEN,
+        ] {
+            assert!(flags.contains(saved));
+        }
+        assert!(!flags.contains(StateFlags::VISIBLE));
+    }
+
+    #[cfg(desktop)]
+    #[test]
+    fn state_file_is_written_on_resize_and_move() {
+        use tauri::{PhysicalPosition, PhysicalSize, WindowEvent};
+
+        let path = std::env::temp_dir().join(format!("echo-window-{}.json", uuid::Uuid::new_v4()));
+        let app = tauri::test::mock_builder()
+            .plugin(
+                window_state_builder()
+                    .with_filename(path.to_string_lossy())
+                    .build(),
+            )
+            .build(tauri::test::mock_context(tauri::test::noop_assets()))
+            .unwrap();
+        let main = tauri::WebviewWindowBuilder::new(&app, "main", tauri::WebviewUrl::default())
+            .build()
+            .unwrap();
+        let window = main.as_ref().window();
+
+        save_geometry(&window, &WindowEvent::Focused(true));
+        assert!(!path.exists());
+
+        save_geometry(&window, &WindowEvent::Resized(PhysicalSize::new(800, 600)));
+        assert!(path.exists());
+        std::fs::remove_file(&path).unwrap();
+
+        save_geometry(&window, &WindowEvent::Moved(PhysicalPosition::new(10, 20)));
+        let saved: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(saved.get("main").is_some());
+    }
+
+    #[cfg(desktop)]
+    #[test]
+    fn another_instance_forwards_its_arguments() {
+        use tauri::Listener;
+
+        let app = tauri::test::mock_app();
+        let (tx, rx) = std::sync::mpsc::channel();
+        app.listen_any("new-instance", move |event| {
+            let _ = tx.send(event.payload().to_string());
+        });
+
+        let args = vec!["echo".to_string(), "echo://exam/42".to_string()];
+        on_another_instance(app.handle(), args, "/home/user".to_string());
+
+        let payload = rx
+            .recv_timeout(std::time::Duration::from_secs(1))
+            .expect("new-instance event");
+        let payload: serde_json::Value = serde_json::from_str(&payload).unwrap();
+        assert_eq!(
+            payload,
+            serde_json::json!({ "args": ["echo", "echo://exam/42"], "cwd": "/home/user" })
+        );
+    }
+}
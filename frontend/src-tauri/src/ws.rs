@@ -0,0 +1,80 @@
+//! A WebSocket to the backend's `/ws` for live updates that would otherwise
+//! need polling. Incoming JSON objects are emitted as `backend-message`
+//! events, the same as those the backend writes to stdout.
+//!
+//! `backend/main.py` doesn't accept WebSockets yet, so `connect_ws` fails
+//! until it serves `/ws` with messages in the `BackendMessage` shape.
+
+use futures_util::stream::SplitSink;
+use futures_util::{SinkExt, StreamExt};
+use tauri::{AppHandle, Manager, State};
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+use crate::backend_proxy::BackendClient;
+use crate::error::EchoError;
+use crate::port::PortState;
+use crate::sidecar::BackendMessage;
+
+type WsSink = SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>;
+
+/// Write half of the open WebSocket, if any.
+#[derive(Default)]
+pub struct WsState(Mutex<Option<WsSink>>);
+
+/// Open the WebSocket, replacing one that is already open.
+#[tauri::command]
+pub async fn connect_ws(
+    handle: AppHandle,
+    port_state: State<'_, Mutex<PortState>>,
+) -> Result<(), EchoError> {
+    let port = port_state
+        .lock()
+        .await
+        .0
+        .ok_or(EchoError::SidecarNotRunning)?;
+    let (url, connector) = handle.state::<BackendClient>().ws(port, "/ws")?;
+    let (stream, _) =
+        tokio_tungstenite::connect_async_tls_with_config(url.as_str(), None, true, connector)
+            .await
+            .map_err(|e| format!("failed to connect to {}: {}", url, e))?;
+    let (sink, mut incoming) = stream.split();
+
+    if let Some(mut old) = handle.state::<WsState>().0.lock().await.replace(sink) {
+        let _ = old.close().await;
+    }
+    log::info!("Connected to {}", url);
+
+    tauri::async_runtime::spawn(async move {
+        while let Some(message) = incoming.next().await {
+            match message {
+                Ok(Message::Text(text)) => match BackendMessage::parse(&text) {
+                    Some(message) => message.emit(&handle),
+                    None => log::warn!("Ignoring WebSocket message that isn't a JSON object"),
+                },
+                Ok(Message::Close(_)) => break,
+                Ok(_) => {}
+                Err(e) => {
+                    log::warn!("WebSocket to the backend failed: {}", e);
+                    break;
+                }
+            }
+        }
+        log::info!("WebSocket to the backend closed");
+    });
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn send_ws_message(
+    msg: serde_json::Value,
+    state: State<'_, WsState>,
+) -> Result<(), EchoError> {
+    let mut sink = state.0.lock().await;
+    let sink = sink.as_mut().ok_or("WebSocket not connected")?;
+    sink.send(Message::text(msg.to_string()))
+        .await
+        .map_err(|e| format!("failed to send WebSocket message: {}", e).into())
+}
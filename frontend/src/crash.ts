@@ -0,0 +1,66 @@
+import { invoke } from '@tauri-apps/api/core'
+import { getCurrentWindow } from '@tauri-apps/api/window'
+
+interface LogEntry {
+  timestamp: number
+  level: 'info' | 'warn'
+  message: string
+}
+
+interface CrashReport {
+  exit_code: number
+  logs: LogEntry[]
+}
+
+const element = <T extends HTMLElement>(id: string) => document.getElementById(id) as T
+const status = element<HTMLParagraphElement>('status')
+
+function diagnostics(report: CrashReport): string {
+  const lines = report.logs.map(
+    entry => `${new Date(entry.timestamp).toISOString()} [${entry.level}] ${entry.message}`
+  )
+  return [`Exit code: ${report.exit_code}`, '', ...lines].join('\n')
+}
+
+async function run(action: () => Promise<void>, done?: string) {
+  status.textContent = ''
+  try {
+    await action()
+    if (done) status.textContent = done
+  } catch (e) {
+    status.textContent = `Failed: ${(e as { message?: string }).message ?? e}`
+  }
+}
+
+async function main() {
+  const report = await invoke<CrashReport | null>('get_crash_report')
+  if (!report) return
+
+  const text = diagnostics(report)
+  element('summary').textContent = `It exited with code ${report.exit_code}.`
+  element('diagnostics').textContent = text
+
+  element('copy').onclick = () =>
+    run(() => invoke('write_clipboard', { text }), 'Copied to clipboard.')
+  element('send').onclick = () =>
+    run(async () => {
+      const version = await invoke('get_app_version')
+      const description = element<HTMLTextAreaElement>('description').value
+      await invoke('send_feedback', {
+        report: {
+          description: `Backend crashed with exit code ${report.exit_code}. ${description}`.trim(),
+          logs: report.logs,
+          screenshot_b64: null,
+          version
+        }
+      })
+    }, 'Report sent, thank you.')
+  element('restart').onclick = () =>
+    run(async () => {
+      await invoke('restart_sidecar')
+      await getCurrentWindow().close()
+    })
+  element('quit').onclick = () => run(() => invoke('quit_app'))
+}
+
+main()
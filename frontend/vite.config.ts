@@ -10,6 +10,14 @@ export default defineConfig({
       '@': fileURLToPath(new URL('./src', import.meta.url))
     }
   },
+  build: {
+    rollupOptions: {
+      input: {
+        main: fileURLToPath(new URL('./index.html', import.meta.url)),
+        crash: fileURLToPath(new URL('./crash.html', import.meta.url))
+      }
+    }
+  },
   server: {
     port: 3000,
     strictPort: true,
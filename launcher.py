@@ -104,7 +104,18 @@ def parse_args():
                         help='Use a specific port instead of auto-detecting')
     parser.add_argument('--tauri', action='store_true',
                         help='Run as Tauri sidecar (skip browser launch)')
-    return parser.parse_args()
+    parser.add_argument('--locale', default=None,
+                        help='UI locale such as zh-CN, exposed to the backend as ECHO_LOCALE')
+    parser.add_argument('--tls', action='store_true',
+                        help='Serve over HTTPS using --cert and --key')
+    parser.add_argument('--cert', default=None,
+                        help='PEM certificate for --tls')
+    parser.add_argument('--key', default=None,
+                        help='PEM private key for --tls')
+    args = parser.parse_args()
+    if args.tls and not (args.cert and args.key):
+        parser.error('--tls requires --cert and --key')
+    return args
 
 
 def main():
@@ -120,6 +131,10 @@ def main():
         os.environ['TAURI_MODE'] = '1'
         print("Running in Tauri sidecar mode")
 
+    if args.locale:
+        os.environ['ECHO_LOCALE'] = args.locale
+        print(f"Using locale {args.locale}")
+
     # Set up SSL certificates for HTTPS connections
     try:
         os.environ['SSL_CERT_FILE'] = certifi.where()
@@ -166,7 +181,8 @@ def main():
         print("=" * 50)
         print("Echo is starting up...")
         print(f"Application data directory: {paths.base_path}")
-        print(f"Server will be available at: http://127.0.0.1:{port}")
+        scheme = "https" if args.tls else "http"
+        print(f"Server will be available at: {scheme}://127.0.0.1:{port}")
         if not args.tauri:
             print("Press Ctrl+C to stop the server")
         print("=" * 50)
@@ -178,7 +194,9 @@ def main():
             port=port,
             log_level="info",
             reload=False,  # Disable reload in packaged version
-            access_log=False  # Reduce log noise
+            access_log=False,  # Reduce log noise
+            ssl_certfile=args.cert if args.tls else None,
+            ssl_keyfile=args.key if args.tls else None,
         )
 
     except KeyboardInterrupt: